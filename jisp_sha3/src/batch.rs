@@ -0,0 +1,227 @@
+//! SHA-3 and SHAKE on many independent messages at once, for hashing large numbers of short messages such as keys or IDs.
+//!
+//! Messages that need the same number of blocks are hashed [WAYS] at a time with the interleaved permutation
+//! [keccak_f1600_many], the messages that are left over go through the
+//! regular one-at-a-time sponge. Either way the digests are the same as those of the functions in [sha3](crate::sha3),
+//! and they are returned in the order of the messages
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::batch::sha3_256_many;
+//! use jisp_sha3::sha3::sha3_256;
+//!
+//! let ids:Vec<Vec<u8>> = (0..10u32).map(|i| i.to_le_bytes().to_vec()).collect();
+//! let messages:Vec<&[u8]> = ids.iter().map(|id| id.as_slice()).collect();
+//!
+//! let hashes = sha3_256_many(&messages);
+//! assert_eq!(hashes[7].to_vec(), sha3_256(&ids[7]));
+//! ```
+use crate::internals::keccak::keccak_f1600_many;
+use crate::internals::sponge::keccak_c;
+use crate::preprocessing::{bytes_to_lane, lane_to_bytes};
+use alloc::{vec, vec::Vec};
+
+/// The number of states the functions in this module permute at the same time
+pub const WAYS:usize = 4;
+
+pub fn sha3_224_many(messages:&[&[u8]]) -> Vec<[u8;28]> {
+    let suffix = vec![false, true];
+    to_arrays(keccak_c_many::<18, WAYS>(messages, &suffix, 224))
+}
+pub fn sha3_256_many(messages:&[&[u8]]) -> Vec<[u8;32]> {
+    let suffix = vec![false, true];
+    to_arrays(keccak_c_many::<17, WAYS>(messages, &suffix, 256))
+}
+pub fn sha3_384_many(messages:&[&[u8]]) -> Vec<[u8;48]> {
+    let suffix = vec![false, true];
+    to_arrays(keccak_c_many::<13, WAYS>(messages, &suffix, 384))
+}
+pub fn sha3_512_many(messages:&[&[u8]]) -> Vec<[u8;64]> {
+    let suffix = vec![false, true];
+    to_arrays(keccak_c_many::<9, WAYS>(messages, &suffix, 512))
+}
+
+/// SHAKE128 with an output of `output_length` bits for every message
+pub fn shake128_many(messages:&[&[u8]], output_length:usize) -> Vec<Vec<u8>> {
+    let suffix = vec![true;4];
+    keccak_c_many::<21, WAYS>(messages, &suffix, output_length)
+}
+
+/// SHAKE256 with an output of `output_length` bits for every message
+pub fn shake256_many(messages:&[&[u8]], output_length:usize) -> Vec<Vec<u8>> {
+    let suffix = vec![true;4];
+    keccak_c_many::<17, WAYS>(messages, &suffix, output_length)
+}
+
+/// Performs [keccak_c] on every message, `N` messages at a time.
+/// With `N = 1` every message goes through the interleaved path on its own, 
+/// `N = 0` and a `RATE` outside of 1 to 24 words are rejected at compile time
+///
+/// # Example
+/// ```
+/// use jisp_sha3::batch::keccak_c_many;
+/// use jisp_sha3::internals::sponge::keccak_c;
+///
+/// let suffix = vec![true;4];
+/// let messages:Vec<&[u8]> = vec![b"a", b"b", b"c", b"d", b"e", b"f", b"g", b"h", b"much longer than the others"];
+///
+/// let outputs = keccak_c_many::<21, 8>(&messages, &suffix, 100);
+/// assert_eq!(outputs[8], keccak_c::<21>(messages[8], &suffix, 100));
+/// ```
+///
+/// ```compile_fail
+/// # use jisp_sha3::batch::keccak_c_many;
+/// keccak_c_many::<21, 0>(&[b"abc"], &vec![true;4], 100);
+/// ```
+///
+/// ```compile_fail
+/// # use jisp_sha3::batch::keccak_c_many;
+/// keccak_c_many::<25, 4>(&[b"abc"], &vec![true;4], 100);
+/// ```
+pub fn keccak_c_many<const RATE:usize, const N:usize>(messages:&[&[u8]], suffix:&[bool], output:usize) -> Vec<Vec<u8>> {
+    const { assert!(N > 0, "keccak_c_many needs to hash at least 1 message at a time!") };
+    const { assert!(RATE > 0 && RATE < 25, "The rate of keccak_c_many has to be between 1 and 24 words!") };
+    let mut results = vec![Vec::new(); messages.len()];
+
+    //group the messages by the number of blocks they need after padding
+    let blocks = |m:&[u8]| (m.len()*8 + suffix.len() + 2).div_ceil(RATE*64);
+    let mut order:Vec<usize> = (0..messages.len()).collect();
+    order.sort_by_key(|i| blocks(messages[*i]));
+
+    for group in order.chunk_by(|a, b| blocks(messages[*a]) == blocks(messages[*b])) {
+        let mut chunks = group.chunks_exact(N);
+        for chunk in &mut chunks {
+            let chunk:[usize;N] = chunk.try_into().unwrap();
+            let outputs = interleaved::<RATE, N>(chunk.map(|i| messages[i]), suffix, output);
+            for (i, result) in chunk.into_iter().zip(outputs) {
+                results[i] = result;
+            }
+        }
+
+        //scalar fallback
+        for i in chunks.remainder() {
+            results[*i] = keccak_c::<RATE>(messages[*i], suffix, output);
+        }
+    }
+
+    results
+}
+
+/// Hashes `N` messages with the same number of blocks in lockstep
+fn interleaved<const RATE:usize, const N:usize>(messages:[&[u8];N], suffix:&[bool], output:usize) -> [Vec<u8>;N] {
+    let mut states = [[0u64;N];25];
+
+    let blocks = (messages[0].len()*8 + suffix.len() + 2).div_ceil(RATE*64);
+    for j in 0..blocks {
+        for k in 0..N {
+            let block = padded_block::<RATE>(messages[k], suffix, j, j + 1 == blocks);
+            for i in 0..RATE {
+                states[i][k] ^= block[i];
+            }
+        }
+        keccak_f1600_many(&mut states, 24);
+    }
+
+    //squeeze bytes
+    let len = output.div_ceil(8);
+    let mut results:[Vec<u8>;N] = core::array::from_fn(|_| Vec::with_capacity(len));
+    loop {
+        for lanes in &states[..RATE] {
+            for k in 0..N {
+                let bytes = lane_to_bytes(lanes[k]);
+                let n = (len - results[k].len()).min(8);
+                results[k].extend_from_slice(&bytes[..n]);
+            }
+            if results[0].len() >= len {
+                return results;
+            }
+        }
+        keccak_f1600_many(&mut states, 24);
+    }
+}
+
+/// Block `j` of the message after adding the suffix and the pad10*1 padding
+fn padded_block<const RATE:usize>(m:&[u8], suffix:&[bool], j:usize, last:bool) -> [u64;RATE] {
+    let mut bytes = [0u8;200];
+    let start = (j*RATE*8).min(m.len());
+    let end = ((j + 1)*RATE*8).min(m.len());
+    bytes[..end - start].copy_from_slice(&m[start..end]);
+
+    //the suffix and the first bit of the padding, which may not all be in the same block
+    let bits = suffix.iter().copied().chain([true]);
+    for (i, bit) in bits.enumerate() {
+        let pos = m.len()*8 + i;
+        if bit && pos / (RATE*64) == j {
+            let pos = pos % (RATE*64);
+            bytes[pos / 8] |= 1 << (pos % 8);
+        }
+    }
+    if last {
+        bytes[RATE*8 - 1] |= 0x80;
+    }
+
+    let mut block = [0u64;RATE];
+    for i in 0..RATE {
+        block[i] = bytes_to_lane(bytes[8*i..8*i + 8].try_into().unwrap());
+    }
+    block
+}
+
+fn to_arrays<const L:usize>(outputs:Vec<Vec<u8>>) -> Vec<[u8;L]> {
+    outputs.into_iter().map(|v| v.try_into().unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha3::{sha3_224, sha3_256, sha3_384, sha3_512, shake128, shake256};
+
+    /// Messages of many lengths, including the lengths around the rate of SHA3-256 and SHAKE128
+    fn messages() -> Vec<Vec<u8>> {
+        let mut lengths:Vec<usize> = (0..40).collect();
+        lengths.extend([71, 72, 103, 104, 134, 135, 136, 137, 143, 166, 167, 168, 169, 300, 1000]);
+        lengths.extend([8, 8, 8, 8, 8, 8, 8, 8, 8, 135, 135, 135]);
+        lengths.iter().enumerate().map(|(i, len)| (0..*len).map(|j| (i*31 + j*7) as u8).collect()).collect()
+    }
+
+    #[test]
+    fn same_as_single_messages() {
+        let owned = messages();
+        let messages:Vec<&[u8]> = owned.iter().map(|m| m.as_slice()).collect();
+
+        let h224 = sha3_224_many(&messages);
+        let h256 = sha3_256_many(&messages);
+        let h384 = sha3_384_many(&messages);
+        let h512 = sha3_512_many(&messages);
+        let s128 = shake128_many(&messages, 1500*8);
+        let s256 = shake256_many(&messages, 77);
+
+        for (i, m) in messages.iter().enumerate() {
+            assert_eq!(h224[i].to_vec(), sha3_224(m));
+            assert_eq!(h256[i].to_vec(), sha3_256(m));
+            assert_eq!(h384[i].to_vec(), sha3_384(m));
+            assert_eq!(h512[i].to_vec(), sha3_512(m));
+            assert_eq!(s128[i], shake128(m, 1500*8));
+            assert_eq!(s256[i], shake256(m, 77));
+        }
+    }
+
+    #[test]
+    fn any_number_of_ways() {
+        let owned = messages();
+        let messages:Vec<&[u8]> = owned.iter().map(|m| m.as_slice()).collect();
+        let suffix = vec![true, false, true, true, false, true, true];
+
+        let scalar = keccak_c_many::<17, 1>(&messages, &suffix, 512);
+        assert_eq!(keccak_c_many::<17, 4>(&messages, &suffix, 512), scalar);
+        assert_eq!(keccak_c_many::<17, 8>(&messages, &suffix, 512), scalar);
+        for (i, m) in messages.iter().enumerate() {
+            assert_eq!(scalar[i], keccak_c::<17>(m, &suffix, 512));
+        }
+    }
+
+    #[test]
+    fn no_messages() {
+        assert!(sha3_256_many(&[]).is_empty());
+    }
+}
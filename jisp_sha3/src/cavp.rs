@@ -0,0 +1,448 @@
+//! A parser and runner for the response files (`.rsp`) of the NIST CAVP tests of SHA-3 and SHAKE, to check this crate against them.
+//!
+//! A response file consists of records of `name = value` lines separated by empty lines, in sections that start with `[name = value]` parameters.
+//! [parse_rsp] reads these into [Record]s, [run_rsp] checks every record against the functions in [sha3](crate::sha3).
+//! It handles the ShortMsg, LongMsg and VariableOut files, for both byte- and bit-oriented messages, and the Monte Carlo files.
+//!
+//! Messages whose length is not a multiple of 8 have their remaining bits in the most significant bits of the last byte,
+//! where whole bytes are read starting at their least significant bit as in FIPS 202. [message_bits] and [cavp_bytes] convert between the two.
+//! Records of the official byte-oriented response files are in the `test_vectors/cavp` directory of this crate.
+//! The samples in `test_vectors/cavp_format` are not NIST data, they only check that the other kinds of files are parsed.
+//! The complete NIST files can be checked by setting `CAVP_DIR` to their directory and running the ignored tests
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::cavp::{parse_rsp, run_rsp, Algorithm};
+//!
+//! let rsp = "\
+//! [L = 256]
+//!
+//! Len = 24
+//! Msg = 616263
+//! MD = 3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532
+//! ";
+//! let records = parse_rsp(rsp).unwrap();
+//! assert_eq!(records[0].param("L"), Some("256"));
+//! assert_eq!(records[0].get("Msg"), Some("616263"));
+//!
+//! assert_eq!(run_rsp(rsp, Algorithm::Sha3_256), Ok(1));
+//! ```
+use crate::error::Error;
+use crate::preprocessing::BitString;
+use crate::sha3::{sha3_224, sha3_224_bits, sha3_256, sha3_256_bits, sha3_384, sha3_384_bits, sha3_512, sha3_512_bits};
+use crate::sha3::{shake128, shake128_bits, shake256, shake256_bits};
+use alloc::{string::String, vec::Vec};
+
+/// The number of hashes between two checkpoints of the Monte Carlo tests
+pub const MONTE_CARLO_ITERATIONS:usize = 1000;
+
+/// One group of `name = value` lines from a response file, together with the parameters of the section it is in
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Record {
+    /// The line of the file the record starts on, counting from 1
+    pub line:usize,
+    /// The `[name = value]` lines of the section, a parameter without a value such as `[Tested for Output of byte-oriented messages]` has an empty value
+    pub params:Vec<(String, String)>,
+    pub fields:Vec<(String, String)>,
+}
+
+impl Record {
+    /// The value of the field `name`
+    pub fn get(&self, name:&str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// The value of the section parameter `name`
+    pub fn param(&self, name:&str) -> Option<&str> {
+        self.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    /// The field `name` decoded from hexadecimal
+    ///
+    /// # Errors
+    /// [Error::MalformedLine] with the line of the record if the field is missing or is not hexadecimal
+    pub fn hex(&self, name:&str) -> Result<Vec<u8>, Error> {
+        let value = self.get(name).ok_or(self.malformed())?;
+        if !value.is_ascii() || !value.len().is_multiple_of(2) {
+            return Err(self.malformed());
+        }
+        (0..value.len()).step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16).map_err(|_| self.malformed()))
+            .collect()
+    }
+
+    /// The field `name` as a decimal number
+    ///
+    /// # Errors
+    /// [Error::MalformedLine] with the line of the record if the field is missing or is not a number
+    pub fn number(&self, name:&str) -> Result<usize, Error> {
+        self.get(name).and_then(|v| v.parse().ok()).ok_or(self.malformed())
+    }
+
+    /// The section parameter `name` as a decimal number
+    ///
+    /// # Errors
+    /// [Error::MalformedLine] with the line of the record if the parameter is missing or is not a number
+    pub fn param_number(&self, name:&str) -> Result<usize, Error> {
+        self.param(name).and_then(|v| v.parse().ok()).ok_or(self.malformed())
+    }
+
+    fn malformed(&self) -> Error {
+        Error::MalformedLine { line: self.line }
+    }
+}
+
+/// Splits a response file into its records, comments starting with `#` are skipped
+///
+/// # Errors
+/// [Error::MalformedLine] for a line that is not a comment, a parameter or a `name = value` pair
+pub fn parse_rsp(text:&str) -> Result<Vec<Record>, Error> {
+    let mut records = Vec::new();
+    let mut params:Vec<(String, String)> = Vec::new();
+    let mut current:Option<Record> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() || line.starts_with('[') {
+            records.extend(current.take());
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(inner) = line.strip_prefix('[') {
+            let inner = inner.strip_suffix(']').ok_or(Error::MalformedLine { line: i + 1 })?;
+            let (name, value) = inner.split_once('=').unwrap_or((inner, ""));
+            let (name, value) = (name.trim(), value.trim());
+            params.retain(|(k, _)| k != name);
+            params.push((name.into(), value.into()));
+            continue;
+        }
+
+        let (name, value) = line.split_once('=').ok_or(Error::MalformedLine { line: i + 1 })?;
+        let record = current.get_or_insert_with(|| Record { line: i + 1, params: params.clone(), fields: Vec::new() });
+        record.fields.push((name.trim().into(), value.trim().into()));
+    }
+    records.extend(current);
+    Ok(records)
+}
+
+/// The functions of [sha3](crate::sha3) that have CAVP tests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+    Shake128,
+    Shake256,
+}
+
+impl Algorithm {
+    /// The length of the digest in bits, or `None` for SHAKE
+    fn digest(&self) -> Option<usize> {
+        match self {
+            Algorithm::Sha3_224 => Some(224),
+            Algorithm::Sha3_256 => Some(256),
+            Algorithm::Sha3_384 => Some(384),
+            Algorithm::Sha3_512 => Some(512),
+            Algorithm::Shake128 | Algorithm::Shake256 => None,
+        }
+    }
+
+    /// The hash of a byte-oriented message with an output of `output` bits, which is ignored by SHA-3
+    fn hash(&self, m:&[u8], output:usize) -> Vec<u8> {
+        match self {
+            Algorithm::Sha3_224 => sha3_224(m),
+            Algorithm::Sha3_256 => sha3_256(m),
+            Algorithm::Sha3_384 => sha3_384(m),
+            Algorithm::Sha3_512 => sha3_512(m),
+            Algorithm::Shake128 => shake128(m, output),
+            Algorithm::Shake256 => shake256(m, output),
+        }
+    }
+
+    fn hash_bits(&self, m:&BitString, output:usize) -> BitString {
+        match self {
+            Algorithm::Sha3_224 => sha3_224_bits(m),
+            Algorithm::Sha3_256 => sha3_256_bits(m),
+            Algorithm::Sha3_384 => sha3_384_bits(m),
+            Algorithm::Sha3_512 => sha3_512_bits(m),
+            Algorithm::Shake128 => shake128_bits(m, output),
+            Algorithm::Shake256 => shake256_bits(m, output),
+        }
+    }
+}
+
+/// The first `len` bits of a CAVP message, where the bits of a partial last byte are taken from its most significant bits
+///
+/// # Panics
+/// If `msg` contains fewer than `len` bits
+///
+/// # Example
+/// ```
+/// use jisp_sha3::cavp::message_bits;
+/// use jisp_sha3::preprocessing::BitString;
+///
+/// // the 5 bit message 11001 from the NIST examples
+/// assert_eq!(message_bits(&[0x98], 5), BitString::from_bytes(&[0x13], 5));
+/// ```
+pub fn message_bits(msg:&[u8], len:usize) -> BitString {
+    if len > msg.len()*8 {panic!("Not enough bytes for a message of {} bits!", len)}
+    let mut bytes = msg[..len.div_ceil(8)].to_vec();
+    if !len.is_multiple_of(8) {
+        let last = bytes.len() - 1;
+        bytes[last] >>= 8 - len % 8;
+    }
+    BitString::from_bytes(&bytes, len)
+}
+
+/// The inverse of [message_bits], the bits of a partial last byte are moved to its most significant bits
+pub fn cavp_bytes(bits:&BitString) -> Vec<u8> {
+    let mut bytes = bits.as_bytes().to_vec();
+    if !bits.len().is_multiple_of(8) {
+        let last = bytes.len() - 1;
+        bytes[last] <<= 8 - bits.len() % 8;
+    }
+    bytes
+}
+
+/// The state of a Monte Carlo test between two checkpoints
+enum MonteCarlo {
+    Sha3 { md:Vec<u8> },
+    /// The output length is in bytes, as are its bounds
+    Shake { output:Vec<u8>, len:usize, min:usize, max:usize },
+}
+
+impl MonteCarlo {
+    /// Runs the iterations up to the next checkpoint, returns the last output and its length in bits
+    fn next(&mut self, algorithm:Algorithm) -> (Vec<u8>, usize) {
+        match self {
+            MonteCarlo::Sha3 { md } => {
+                for _ in 0..MONTE_CARLO_ITERATIONS {
+                    *md = algorithm.hash(md, 0);
+                }
+                (md.clone(), md.len()*8)
+            }
+            MonteCarlo::Shake { output, len, min, max } => {
+                let mut last = *len;
+                for _ in 0..MONTE_CARLO_ITERATIONS {
+                    //the 128 leftmost bits of the previous output
+                    let mut m = [0u8;16];
+                    let n = output.len().min(16);
+                    m[..n].copy_from_slice(&output[..n]);
+
+                    *output = algorithm.hash(&m, *len*8);
+                    last = *len;
+                    let rightmost = u16::from_be_bytes([output[output.len() - 2], output[output.len() - 1]]) as usize;
+                    *len = *min + rightmost % (*max - *min + 1);
+                }
+                (output.clone(), last*8)
+            }
+        }
+    }
+}
+
+/// Checks every record of a response file for `algorithm` and returns the number of records that were checked.
+///
+/// A record with a `Seed` starts the Monte Carlo test of SHA-3, a record with only a `Msg` starts the one of SHAKE,
+/// after which every record with a `COUNT` is the next checkpoint. Any other record with a `Msg` is hashed on its own,
+/// where the length of the message is its `Len` field or the `Input Length` parameter, and the output length of SHAKE
+/// is its `Outputlen` field or parameter
+///
+/// # Errors
+/// - [Error::MalformedLine] if the file can not be parsed or a record is missing a field,
+///   or if the output lengths of the Monte Carlo test of SHAKE are not a range of at least 16 bits
+/// - [Error::VectorMismatch] with the line of the first record whose expected output does not match
+pub fn run_rsp(text:&str, algorithm:Algorithm) -> Result<usize, Error> {
+    let mut checked = 0;
+    let mut monte_carlo:Option<(MonteCarlo, usize)> = None;
+
+    for record in parse_rsp(text)? {
+        let mismatch = Error::VectorMismatch { line: record.line };
+
+        //the start of a Monte Carlo test
+        if record.get("Seed").is_some() {
+            monte_carlo = Some((MonteCarlo::Sha3 { md: record.hex("Seed")? }, 0));
+            continue;
+        }
+        if record.get("Msg").is_some() && record.get("Len").is_none() && record.get("COUNT").is_none() {
+            let min = record.param_number("Minimum Output Length (bits)")? / 8;
+            let max = record.param_number("Maximum Output Length (bits)")? / 8;
+            //the next output length is taken from the last 16 bits of the previous output
+            if min < 2 || max < min {
+                return Err(Error::MalformedLine { line: record.line });
+            }
+            monte_carlo = Some((MonteCarlo::Shake { output: record.hex("Msg")?, len: max, min, max }, 0));
+            continue;
+        }
+
+        //the next checkpoint of a Monte Carlo test
+        if record.get("Msg").is_none() {
+            let (state, count) = monte_carlo.as_mut().ok_or(Error::MalformedLine { line: record.line })?;
+            if record.number("COUNT")? != *count {
+                return Err(mismatch);
+            }
+            let (output, len) = state.next(algorithm);
+            let expected = if algorithm.digest().is_some() {record.hex("MD")?} else {record.hex("Output")?};
+            if output != expected || (algorithm.digest().is_none() && record.number("Outputlen")? != len) {
+                return Err(mismatch);
+            }
+            *count += 1;
+            checked += 1;
+            continue;
+        }
+
+        //a single message
+        let msg = record.hex("Msg")?;
+        let len = match record.get("Len") {
+            Some(_) => record.number("Len")?,
+            None => record.param_number("Input Length")?,
+        };
+        let (output, expected) = match algorithm.digest() {
+            Some(digest) => (digest, record.hex("MD")?),
+            None => {
+                let output = record.number("Outputlen").or(record.param_number("Outputlen"))?;
+                (output, record.hex("Output")?)
+            }
+        };
+        if len > msg.len()*8 {
+            return Err(Error::MalformedLine { line: record.line });
+        }
+
+        let hash = if len.is_multiple_of(8) && output.is_multiple_of(8) {
+            algorithm.hash(&msg[..len / 8], output)
+        } else {
+            cavp_bytes(&algorithm.hash_bits(&message_bits(&msg, len), output))
+        };
+        if hash != expected {
+            return Err(mismatch);
+        }
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+
+    macro_rules! rsp {
+        ($name:literal) => {
+            include_str!(concat!("../test_vectors/cavp/", $name, ".rsp"))
+        };
+    }
+
+    macro_rules! format_sample {
+        ($name:literal) => {
+            include_str!(concat!("../test_vectors/cavp_format/", $name, ".rsp"))
+        };
+    }
+
+    #[test]
+    fn parsing() {
+        let records = parse_rsp("# comment\n[L = 224]\n[Tested]\n\nLen = 0\nMsg = 00\n\n\n[L = 256]\nCOUNT = 3\n").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].line, 5);
+        assert_eq!(records[0].param("L"), Some("224"));
+        assert_eq!(records[0].param("Tested"), Some(""));
+        assert_eq!(records[0].number("Len"), Ok(0));
+        assert_eq!(records[0].hex("Msg"), Ok(vec![0]));
+        assert_eq!(records[1].param_number("L"), Ok(256));
+        assert_eq!(records[1].number("COUNT"), Ok(3));
+
+        assert_eq!(parse_rsp("Len = 0\nMsg\n"), Err(Error::MalformedLine { line: 2 }));
+        assert_eq!(parse_rsp("[L = 224\n"), Err(Error::MalformedLine { line: 1 }));
+        assert_eq!(records[0].hex("MD"), Err(Error::MalformedLine { line: 5 }));
+        assert_eq!(parse_rsp("Msg = 0g\n").unwrap()[0].hex("Msg"), Err(Error::MalformedLine { line: 1 }));
+        assert_eq!(parse_rsp("Msg = aé0\n").unwrap()[0].hex("Msg"), Err(Error::MalformedLine { line: 1 }));
+    }
+
+    #[test]
+    fn bit_conversion() {
+        let bits = message_bits(&[0xab, 0xe0], 11);
+        assert_eq!(bits, BitString::from_bytes(&[0xab, 0x07], 11));
+        assert_eq!(cavp_bytes(&bits), [0xab, 0xe0]);
+    }
+
+    // Records of the NIST files
+    #[test]
+    fn nist_records() {
+        assert_eq!(run_rsp(rsp!("SHA3_224ShortMsg"), Algorithm::Sha3_224), Ok(6));
+        assert_eq!(run_rsp(rsp!("SHA3_256ShortMsg"), Algorithm::Sha3_256), Ok(7));
+        assert_eq!(run_rsp(rsp!("SHA3_384ShortMsg"), Algorithm::Sha3_384), Ok(5));
+        assert_eq!(run_rsp(rsp!("SHA3_512ShortMsg"), Algorithm::Sha3_512), Ok(3));
+        assert_eq!(run_rsp(rsp!("SHA3_256LongMsg"), Algorithm::Sha3_256), Ok(1));
+        assert_eq!(run_rsp(rsp!("SHAKE128ShortMsg"), Algorithm::Shake128), Ok(2));
+        assert_eq!(run_rsp(rsp!("SHAKE256ShortMsg"), Algorithm::Shake256), Ok(2));
+    }
+
+    // The other kinds of files, whose outputs were computed outside of this crate but are not from NIST
+    #[test]
+    fn format_samples() {
+        assert_eq!(run_rsp(format_sample!("SHA3_256ShortMsg_bits"), Algorithm::Sha3_256), Ok(6));
+        assert_eq!(run_rsp(format_sample!("SHAKE128ShortMsg_bits"), Algorithm::Shake128), Ok(6));
+        assert_eq!(run_rsp(format_sample!("SHAKE128LongMsg"), Algorithm::Shake128), Ok(3));
+        assert_eq!(run_rsp(format_sample!("SHAKE128VariableOut"), Algorithm::Shake128), Ok(4));
+        assert_eq!(run_rsp(format_sample!("SHAKE256VariableOut"), Algorithm::Shake256), Ok(4));
+        assert_eq!(run_rsp(format_sample!("SHAKE128VariableOut_bits"), Algorithm::Shake128), Ok(2));
+
+        assert_eq!(run_rsp(format_sample!("SHA3_224Monte"), Algorithm::Sha3_224), Ok(3));
+        assert_eq!(run_rsp(format_sample!("SHA3_256Monte"), Algorithm::Sha3_256), Ok(3));
+        assert_eq!(run_rsp(format_sample!("SHA3_384Monte"), Algorithm::Sha3_384), Ok(3));
+        assert_eq!(run_rsp(format_sample!("SHA3_512Monte"), Algorithm::Sha3_512), Ok(3));
+        assert_eq!(run_rsp(format_sample!("SHAKE128Monte"), Algorithm::Shake128), Ok(3));
+        assert_eq!(run_rsp(format_sample!("SHAKE256Monte"), Algorithm::Shake256), Ok(3));
+    }
+
+    // Every response file of the NIST CAVP in the directory `CAVP_DIR`, run with `cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn nist_files() {
+        let dir = std::env::var("CAVP_DIR").expect("CAVP_DIR should be the directory of the NIST response files");
+        let algorithms = [
+            ("SHA3_224", Algorithm::Sha3_224), ("SHA3_256", Algorithm::Sha3_256), ("SHA3_384", Algorithm::Sha3_384),
+            ("SHA3_512", Algorithm::Sha3_512), ("SHAKE128", Algorithm::Shake128), ("SHAKE256", Algorithm::Shake256),
+        ];
+        let mut files = 0;
+        for (prefix, algorithm) in algorithms {
+            for kind in ["ShortMsg", "LongMsg", "Monte", "VariableOut"] {
+                let path = std::format!("{}/{}{}.rsp", dir, prefix, kind);
+                if let Ok(text) = std::fs::read_to_string(&path) {
+                    assert!(run_rsp(&text, algorithm).unwrap_or_else(|e| panic!("{}: {}", path, e)) > 0);
+                    files += 1;
+                }
+            }
+        }
+        assert!(files > 0, "no response files found in {}", dir);
+    }
+
+    #[test]
+    fn mismatches_are_reported() {
+        let rsp = rsp!("SHA3_256ShortMsg");
+        assert_eq!(run_rsp(rsp, Algorithm::Sha3_224), Err(Error::VectorMismatch { line: 7 }));
+
+        // a single changed digit of the 4th record
+        let md = rsp.lines().filter(|l| l.starts_with("MD")).nth(3).unwrap();
+        let line = rsp.lines().position(|l| l == md).unwrap() + 1;
+        let mut wrong = String::from(md);
+        let last = if wrong.ends_with('0') {"1"} else {"0"};
+        wrong.replace_range(wrong.len() - 1.., last);
+        assert_eq!(run_rsp(&rsp.replace(md, &wrong), Algorithm::Sha3_256), Err(Error::VectorMismatch { line: line - 2 }));
+
+        // output lengths of SHAKE that are too short or not a range
+        let monte_carlo = |min:usize, max:usize| std::format!("\
+            [Minimum Output Length (bits) = {}]\n[Maximum Output Length (bits) = {}]\n\n\
+            Msg = 00\n\nCOUNT = 0\nOutputlen = 8\nOutput = 00\n", min, max);
+        assert_eq!(run_rsp(&monte_carlo(8, 1024), Algorithm::Shake128), Err(Error::MalformedLine { line: 4 }));
+        assert_eq!(run_rsp(&monte_carlo(0, 8), Algorithm::Shake128), Err(Error::MalformedLine { line: 4 }));
+        assert_eq!(run_rsp(&monte_carlo(1024, 128), Algorithm::Shake256), Err(Error::MalformedLine { line: 4 }));
+
+        // a checkpoint without a Monte Carlo test before it
+        assert_eq!(run_rsp("COUNT = 0\nMD = 00\n", Algorithm::Sha3_256), Err(Error::MalformedLine { line: 1 }));
+    }
+}
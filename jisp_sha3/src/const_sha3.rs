@@ -0,0 +1,160 @@
+//! The SHA-3 and SHAKE functions as `const fn`, for hashing constant strings such as protocol labels at compile time.
+//!
+//! They give the same output as the functions in [sha3](crate::sha3), but return fixed size arrays and never allocate.
+//! The output length of SHAKE is given by the size of the array, in bytes.
+//! These run on [keccak_f1600_const], which is slower than the runtime permutation, so use them for constants only
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::const_sha3::sha3_256_const;
+//!
+//! const TAG:[u8;32] = sha3_256_const(b"my-protocol-v1");
+//!
+//! let mut hasher = jisp_sha3::sha3::Sha3_256::new();
+//! hasher.update(b"my-protocol-v1");
+//! let mut hash = [0u8; 32];
+//! hasher.finalize_into(&mut hash);
+//!
+//! assert_eq!(TAG, hash);
+//! ```
+use crate::internals::keccak::keccak_f1600_const;
+use crate::preprocessing::{bytes_to_lane, lane_to_bytes};
+
+/// The suffix `01` of SHA-3 and `1111` of SHAKE, each followed by the first bit of the padding
+const SHA3_DOMAIN:u8 = 0x06;
+const SHAKE_DOMAIN:u8 = 0x1F;
+
+pub const fn sha3_224_const(m:&[u8]) -> [u8;28] {
+    keccak_c_const::<18, 28>(m, SHA3_DOMAIN)
+}
+pub const fn sha3_256_const(m:&[u8]) -> [u8;32] {
+    keccak_c_const::<17, 32>(m, SHA3_DOMAIN)
+}
+pub const fn sha3_384_const(m:&[u8]) -> [u8;48] {
+    keccak_c_const::<13, 48>(m, SHA3_DOMAIN)
+}
+pub const fn sha3_512_const(m:&[u8]) -> [u8;64] {
+    keccak_c_const::<9, 64>(m, SHA3_DOMAIN)
+}
+
+/// SHAKE128 with an output of `N` bytes
+///
+/// # Example
+/// ```
+/// use jisp_sha3::const_sha3::shake128_const;
+///
+/// const MASK:[u8;4] = shake128_const(b"");
+/// assert_eq!(MASK, [0x7f, 0x9c, 0x2b, 0xa4]);
+/// ```
+pub const fn shake128_const<const N:usize>(m:&[u8]) -> [u8;N] {
+    keccak_c_const::<21, N>(m, SHAKE_DOMAIN)
+}
+
+/// SHAKE256 with an output of `N` bytes
+pub const fn shake256_const<const N:usize>(m:&[u8]) -> [u8;N] {
+    keccak_c_const::<17, N>(m, SHAKE_DOMAIN)
+}
+
+/// The keccak\[c\] sponge with an output of `N` bytes, where `domain` holds the suffix bits followed by the first bit of pad10*1
+const fn keccak_c_const<const RATE:usize, const N:usize>(m:&[u8], domain:u8) -> [u8;N] {
+    let mut state = [0u64;25];
+
+    //absorb the full blocks
+    let mut start = 0;
+    while m.len() - start >= RATE*8 {
+        let mut i = 0;
+        while i < RATE {
+            let j = start + 8*i;
+            state[i] ^= bytes_to_lane([m[j], m[j + 1], m[j + 2], m[j + 3], m[j + 4], m[j + 5], m[j + 6], m[j + 7]]);
+            i += 1;
+        }
+        state = keccak_f1600_const(state, 24);
+        start += RATE*8;
+    }
+
+    //the remaining bytes with the padding
+    let mut block = [0u8;200];
+    let mut i = 0;
+    while start + i < m.len() {
+        block[i] = m[start + i];
+        i += 1;
+    }
+    block[i] ^= domain;
+    block[RATE*8 - 1] ^= 0x80;
+
+    let mut i = 0;
+    while i < RATE {
+        let j = 8*i;
+        state[i] ^= bytes_to_lane([block[j], block[j + 1], block[j + 2], block[j + 3], block[j + 4], block[j + 5], block[j + 6], block[j + 7]]);
+        i += 1;
+    }
+    state = keccak_f1600_const(state, 24);
+
+    //squeeze
+    let mut res = [0u8;N];
+    let mut pos = 0;
+    let mut i = 0;
+    while i < N {
+        if pos == RATE*8 {
+            state = keccak_f1600_const(state, 24);
+            pos = 0;
+        }
+        res[i] = lane_to_bytes(state[pos / 8])[pos % 8];
+        pos += 1;
+        i += 1;
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha3::{Sha3, Shake};
+
+    fn runtime_sha3<const RATE:usize, const DIGEST:usize, const N:usize>(m:&[u8]) -> [u8;N] {
+        let mut hasher = Sha3::<RATE, DIGEST>::new();
+        hasher.update(m);
+        let mut res = [0u8;N];
+        hasher.finalize_into(&mut res);
+        res
+    }
+
+    fn runtime_shake<const RATE:usize, const N:usize>(m:&[u8]) -> [u8;N] {
+        let mut hasher = Shake::<RATE>::new();
+        hasher.update(m);
+        let mut res = [0u8;N];
+        hasher.finalize_xof().read(&mut res);
+        res
+    }
+
+    #[test]
+    fn compile_time_digests() {
+        const ABC:[u8;32] = sha3_256_const(b"abc");
+        const EMPTY:[u8;64] = shake256_const(b"");
+        assert_eq!(ABC[..4], [0x3a, 0x98, 0x5d, 0xa7]);
+        assert_eq!(EMPTY[..4], [0x46, 0xb9, 0xdd, 0x2b]);
+    }
+
+    #[test]
+    fn same_as_runtime() {
+        let m:[u8;400] = core::array::from_fn(|i| (i*7) as u8);
+        // the lengths around the rate of every variant
+        for len in [0, 1, 71, 72, 73, 103, 104, 135, 136, 137, 143, 144, 167, 168, 169, 400] {
+            let m = &m[..len];
+            assert_eq!(sha3_224_const(m), runtime_sha3::<18, 224, 28>(m));
+            assert_eq!(sha3_256_const(m), runtime_sha3::<17, 256, 32>(m));
+            assert_eq!(sha3_384_const(m), runtime_sha3::<13, 384, 48>(m));
+            assert_eq!(sha3_512_const(m), runtime_sha3::<9, 512, 64>(m));
+            assert_eq!(shake128_const::<500>(m), runtime_shake::<21, 500>(m));
+            assert_eq!(shake256_const::<300>(m), runtime_shake::<17, 300>(m));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn same_as_one_shot() {
+        let m = b"my-protocol-v1";
+        assert_eq!(sha3_256_const(m).to_vec(), crate::sha3::sha3_256(m));
+        assert_eq!(shake128_const::<200>(m).to_vec(), crate::sha3::shake128(m, 200*8));
+    }
+}
@@ -0,0 +1,141 @@
+//! cSHAKE128 and cSHAKE256 as defined in NIST SP 800-185, the customizable versions of [SHAKE](crate::sha3::shake128). 
+//! 
+//! Next to the message they take a function name `n`, which is reserved for functions defined by NIST, 
+//! and a customization string `s` which can be freely chosen to separate different uses of the same function. 
+//! When both strings are empty cSHAKE is equal to SHAKE
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::cshake::cshake128;
+//! use jisp_sha3::printer::print_bytes_be;
+//!
+//! let hash = cshake128(&[0, 1, 2, 3], 256, b"", b"Email Signature");
+//!
+//! let expected = "c1c36925 b6409a04 f1b504fc bca9d82b 4017277c b5ed2b20 65fc1d38 14d5aaf5";
+//! assert_eq!(print_bytes_be(&hash), expected);
+//! ```
+use crate::internals::sponge::{keccak_c, Sponge, SpongeReader};
+use crate::preprocessing::{bytepad, encode_string};
+use alloc::{vec, vec::Vec};
+
+pub fn cshake128(x:&[u8], l:usize, n:&[u8], s:&[u8]) -> Vec<u8> {
+    cshake::<21>(x, l, n, s)
+}
+
+pub fn cshake256(x:&[u8], l:usize, n:&[u8], s:&[u8]) -> Vec<u8> {
+    cshake::<17>(x, l, n, s)
+}
+
+fn cshake<const RATE:usize>(x:&[u8], l:usize, n:&[u8], s:&[u8]) -> Vec<u8> {
+    if n.is_empty() && s.is_empty() {
+        let suffix = vec![true;4];
+        return keccak_c::<RATE>(x, &suffix, l);
+    }
+
+    let suffix = vec![false;2];
+    let mut m = prefix::<RATE>(n, s);
+    m.extend_from_slice(x);
+    keccak_c::<RATE>(&m, &suffix, l)
+}
+
+/// `bytepad(encode_string(n) || encode_string(s), rate)`, which is absorbed before the message
+fn prefix<const RATE:usize>(n:&[u8], s:&[u8]) -> Vec<u8> {
+    let mut res = encode_string(n);
+    res.extend(encode_string(s));
+    bytepad(&res, RATE*8)
+}
+
+/// Incremental version of cSHAKE, use one of the aliases [CShake128] or [CShake256]
+///
+/// # Example
+/// ```
+/// use jisp_sha3::cshake::{CShake256, cshake256};
+///
+/// let mut hasher = CShake256::new(b"", b"my protocol");
+/// hasher.update(b"first part, ");
+/// hasher.update(b"second part");
+///
+/// let expected = cshake256(b"first part, second part", 512, b"", b"my protocol");
+/// assert_eq!(hasher.finalize(512), expected);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CShake<const RATE:usize> {
+    sponge:Sponge<RATE>,
+}
+
+pub type CShake128 = CShake<21>;
+pub type CShake256 = CShake<17>;
+
+impl<const RATE:usize> CShake<RATE> {
+    pub fn new(n:&[u8], s:&[u8]) -> Self {
+        if n.is_empty() && s.is_empty() {
+            let suffix = vec![true;4];
+            return CShake { sponge: Sponge::new(&suffix) };
+        }
+
+        let suffix = vec![false;2];
+        let mut sponge = Sponge::new(&suffix);
+        sponge.absorb(&prefix::<RATE>(n, s));
+        CShake { sponge }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        self.sponge.absorb(m);
+    }
+
+    /// Finishes the message and returns `l` bits of output
+    pub fn finalize(self, l:usize) -> Vec<u8> {
+        self.sponge.squeeze(l)
+    }
+
+    /// Finishes the message and returns a reader for an output of unknown length
+    pub fn finalize_xof(self) -> SpongeReader<RATE> {
+        self.sponge.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::to_hex;
+    use crate::sha3::{shake128, shake256};
+
+    // The samples published by NIST for SP 800-185
+    #[test]
+    fn nist_samples() {
+        let short:Vec<u8> = (0..4).collect();
+        let long:Vec<u8> = (0..200).collect();
+        let s = b"Email Signature";
+
+        assert_eq!(to_hex(&cshake128(&short, 256, b"", s)), "c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5");
+        assert_eq!(to_hex(&cshake128(&long, 256, b"", s)), "c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b");
+        assert_eq!(to_hex(&cshake256(&short, 512, b"", s)), 
+            "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd164020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c");
+        assert_eq!(to_hex(&cshake256(&long, 512, b"", s)), 
+            "07dc27b11e51fbac75bc7b3c1d983e8b4b85fb1defaf218912ac86430273091727f42b17ed1df63e8ec118f04b23633c1dfb1574c8fb55cb45da8e25afb092bb");
+    }
+
+    #[test]
+    fn empty_strings_are_shake() {
+        let m = b"abc";
+        assert_eq!(cshake128(m, 400, b"", b""), shake128(m, 400));
+        assert_eq!(cshake256(m, 400, b"", b""), shake256(m, 400));
+        assert_eq!(CShake128::new(b"", b"").finalize(400), shake128(b"", 400));
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let m:Vec<u8> = (0..200).collect();
+        let mut hasher = CShake128::new(b"name", b"");
+        for part in m.chunks(33) {
+            hasher.update(part);
+        }
+        let mut reader = hasher.finalize_xof();
+        let mut res = vec![0u8; 200];
+        reader.read(&mut res[..100]);
+        reader.read(&mut res[100..]);
+
+        assert_eq!(res, cshake128(&m, 200*8, b"name", b""));
+    }
+}
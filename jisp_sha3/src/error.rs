@@ -0,0 +1,30 @@
+//! The errors returned by the functions in this crate that can fail on valid input
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The authentication tag does not match the message, nothing of the message is released
+    AuthenticationFailed,
+    /// More output was requested than the function can produce
+    OutputTooLong { requested:usize, max:usize },
+    /// The domain separation tag is empty, which is not allowed by RFC 9380
+    EmptyDst,
+    /// A line of a [CAVP response file](crate::cavp) that can not be parsed, or the first line of a record that is missing a field
+    MalformedLine { line:usize },
+    /// The output for the record of a [CAVP response file](crate::cavp) starting at this line does not match the expected output
+    VectorMismatch { line:usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AuthenticationFailed => write!(f, "the authentication tag does not match the message"),
+            Error::OutputTooLong { requested, max } => write!(f, "an output of {} bytes was requested, but at most {} bytes are allowed", requested, max),
+            Error::EmptyDst => write!(f, "the domain separation tag is empty"),
+            Error::MalformedLine { line } => write!(f, "line {} of the response file is malformed", line),
+            Error::VectorMismatch { line } => write!(f, "the output for the record at line {} does not match", line),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,173 @@
+//! `expand_message_xof` from RFC 9380 with SHAKE128 and SHAKE256, which turns a message into uniformly random bytes for hashing to a field or curve.
+//!
+//! The output is `SHAKE(msg || I2OSP(len, 2) || DST || I2OSP(len(DST), 1))` where `len` is the number of output bytes,
+//! so outputs of different lengths are unrelated to each other. The domain separation tag `DST` must not be empty.
+//! A tag longer than 255 bytes is replaced by `SHAKE("H2C-OVERSIZE-DST-" || DST)` of 32 bytes for SHAKE128 and 64 bytes for SHAKE256.
+//! The output length is given by the size of the buffer and can be at most 65535 bytes
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::expand_message::expand_message_shake128;
+//!
+//! let mut uniform_bytes = [0u8; 32];
+//! expand_message_shake128(b"", b"QUUX-V01-CS02-with-expander-SHAKE128", &mut uniform_bytes).unwrap();
+//! assert_eq!(uniform_bytes[..4], [0x86, 0x51, 0x8c, 0x9c]);
+//!
+//! assert!(expand_message_shake128(b"", b"", &mut uniform_bytes).is_err());
+//! ```
+use crate::error::Error;
+use crate::sha3::Shake;
+
+/// The largest output, its length has to fit in 2 bytes
+pub const MAX_OUTPUT:usize = 65535;
+
+/// The longest domain separation tag that is used as it is, longer tags are hashed first
+pub const MAX_DST:usize = 255;
+
+/// # Errors
+/// [Error::EmptyDst] if the domain separation tag is empty, [Error::OutputTooLong] if `out` is longer than [MAX_OUTPUT] bytes
+pub fn expand_message_shake128(msg:&[u8], dst:&[u8], out:&mut [u8]) -> Result<(), Error> {
+    expand_message_xof::<21>(msg, dst, out)
+}
+
+/// # Errors
+/// [Error::EmptyDst] if the domain separation tag is empty, [Error::OutputTooLong] if `out` is longer than [MAX_OUTPUT] bytes
+pub fn expand_message_shake256(msg:&[u8], dst:&[u8], out:&mut [u8]) -> Result<(), Error> {
+    expand_message_xof::<17>(msg, dst, out)
+}
+
+/// `expand_message_xof` on SHAKE with a rate of `RATE` words, the oversized tags are hashed to `2k` bits where `k` is the security level,
+/// which is the capacity of the sponge
+fn expand_message_xof<const RATE:usize>(msg:&[u8], dst:&[u8], out:&mut [u8]) -> Result<(), Error> {
+    if dst.is_empty() {
+        return Err(Error::EmptyDst);
+    }
+    if out.len() > MAX_OUTPUT {
+        return Err(Error::OutputTooLong { requested: out.len(), max: MAX_OUTPUT });
+    }
+
+    //the oversized DST rule
+    let mut hashed = [0u8;64];
+    let dst = if dst.len() > MAX_DST {
+        let len = 200 - RATE*8;
+        let mut hasher = Shake::<RATE>::new();
+        hasher.update(b"H2C-OVERSIZE-DST-");
+        hasher.update(dst);
+        hasher.finalize_xof().read(&mut hashed[..len]);
+        &hashed[..len]
+    } else {
+        dst
+    };
+
+    let mut hasher = Shake::<RATE>::new();
+    hasher.update(msg);
+    hasher.update(&(out.len() as u16).to_be_bytes());
+    hasher.update(dst);
+    hasher.update(&[dst.len() as u8]);
+    hasher.finalize_xof().read(out);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::from_hex;
+
+    /// Checks the outputs of 32 and 128 bytes for the five messages of the test vectors in RFC 9380
+    fn check(f:impl Fn(&[u8], &[u8], &mut [u8]) -> Result<(), Error>, dst:&[u8], expected:[&str;10]) {
+        let q:[u8;133] = core::array::from_fn(|i| if i < 5 {b"q128_"[i]} else {b'q'});
+        let a:[u8;517] = core::array::from_fn(|i| if i < 5 {b"a512_"[i]} else {b'a'});
+        let messages:[&[u8];5] = [b"", b"abc", b"abcdef0123456789", &q, &a];
+
+        for (i, expected) in expected.into_iter().enumerate() {
+            let len = if i < 5 {32} else {128};
+            let mut out = [0u8;128];
+            f(messages[i % 5], dst, &mut out[..len]).unwrap();
+            assert_eq!(out[..len], from_hex(expected));
+        }
+    }
+
+    /// The long tag of the test vectors, padded with ones to 256 bytes
+    fn long_dst(prefix:&[u8]) -> [u8;256] {
+        core::array::from_fn(|i| if i < prefix.len() {prefix[i]} else {b'1'})
+    }
+
+    // The test vectors of RFC 9380, appendix K.6
+    #[test]
+    fn shake128_vectors() {
+        check(expand_message_shake128, b"QUUX-V01-CS02-with-expander-SHAKE128", [
+            "86518c9cd86581486e9485aa74ab35ba150d1c75c88e26b7043e44e2acd735a2",
+            "8696af52a4d862417c0763556073f47bc9b9ba43c99b505305cb1ec04a9ab468",
+            "912c58deac4821c3509dbefa094df54b34b8f5d01a191d1d3108a2c89077acca",
+            "1adbcc448aef2a0cebc71dac9f756b22e51839d348e031e63b33ebb50faeaf3f",
+            "df3447cc5f3e9a77da10f819218ddf31342c310778e0e4ef72bbaecee786a4fe",
+            "7314ff1a155a2fb99a0171dc71b89ab6e3b2b7d59e38e64419b8b6294d03ffee42491f11370261f436220ef787f8f76f5b26bdcd850071920ce023f3ac46847744f4612b8714db8f5db83205b2e625d95afd7d7b4d3094d3bdde815f52850bb41ead9822e08f22cf41d615a303b0d9dde73263c049a7b9898208003a739a2e57",
+            "c952f0c8e529ca8824acc6a4cab0e782fc3648c563ddb00da7399f2ae35654f4860ec671db2356ba7baa55a34a9d7f79197b60ddae6e64768a37d699a78323496db3878c8d64d909d0f8a7de4927dcab0d3dbbc26cb20a49eceb0530b431cdf47bc8c0fa3e0d88f53b318b6739fbed7d7634974f1b5c386d6230c76260d5337a",
+            "19b65ee7afec6ac06a144f2d6134f08eeec185f1a890fe34e68f0e377b7d0312883c048d9b8a1d6ecc3b541cb4987c26f45e0c82691ea299b5e6889bbfe589153016d8131717ba26f07c3c14ffbef1f3eff9752e5b6183f43871a78219a75e7000fbac6a7072e2b83c790a3a5aecd9d14be79f9fd4fb180960a3772e08680495",
+            "ca1b56861482b16eae0f4a26212112362fcc2d76dcc80c93c4182ed66c5113fe41733ed68be2942a3487394317f3379856f4822a611735e50528a60e7ade8ec8c71670fec6661e2c59a09ed36386513221688b35dc47e3c3111ee8c67ff49579089d661caa29db1ef10eb6eace575bf3dc9806e7c4016bd50f3c0e2a6481ee6d",
+            "9d763a5ce58f65c91531b4100c7266d479a5d9777ba761693d052acd37d149e7ac91c796a10b919cd74a591a1e38719fb91b7203e2af31eac3bff7ead2c195af7d88b8bc0a8adf3d1e90ab9bed6ddc2b7f655dd86c730bdeaea884e73741097142c92f0e3fc1811b699ba593c7fbd81da288a29d423df831652e3a01a9374999",
+        ]);
+    }
+
+    #[test]
+    fn shake128_long_dst_vectors() {
+        check(expand_message_shake128, &long_dst(b"QUUX-V01-CS02-with-expander-SHAKE128-long-DST-"), [
+            "827c6216330a122352312bccc0c8d6e7a146c5257a776dbd9ad9d75cd880fc53",
+            "690c8d82c7213b4282c6cb41c00e31ea1d3e2005f93ad19bbf6da40f15790c5c",
+            "979e3a15064afbbcf99f62cc09fa9c85028afcf3f825eb0711894dcfc2f57057",
+            "c5a9220962d9edc212c063f4f65b609755a1ed96e62f9db5d1fd6adb5a8dc52b",
+            "f7b96a5901af5d78ce1d071d9c383cac66a1dfadb508300ec6aeaea0d62d5d62",
+            "3890dbab00a2830be398524b71c2713bbef5f4884ac2e6f070b092effdb19208c7df943dc5dcbaee3094a78c267ef276632ee2c8ea0c05363c94b6348500fae4208345dd3475fe0c834c2beac7fa7bc181692fb728c0a53d809fc8111495222ce0f38468b11becb15b32060218e285c57a60162c2c8bb5b6bded13973cd41819",
+            "41b7ffa7a301b5c1441495ebb9774e2a53dbbf4e54b9a1af6a20fd41eafd69ef7b9418599c5545b1ee422f363642b01d4a53449313f68da3e49dddb9cd25b97465170537d45dcbdf92391b5bdff344db4bd06311a05bca7dcd360b6caec849c299133e5c9194f4e15e3e23cfaab4003fab776f6ac0bfae9144c6e2e1c62e7d57",
+            "55317e4a21318472cd2290c3082957e1242241d9e0d04f47026f03401643131401071f01aa03038b2783e795bdfa8a3541c194ad5de7cb9c225133e24af6c86e748deb52e560569bd54ef4dac03465111a3a44b0ea490fb36777ff8ea9f1a8a3e8e0de3cf0880b4b2f8dd37d3a85a8b82375aee4fa0e909f9763319b55778e71",
+            "19fdd2639f082e31c77717ac9bb032a22ff0958382b2dbb39020cdc78f0da43305414806abf9a561cb2d0067eb2f7bc544482f75623438ed4b4e39dd9e6e2909dd858bd8f1d57cd0fce2d3150d90aa67b4498bdf2df98c0100dd1a173436ba5d0df6be1defb0b2ce55ccd2f4fc05eb7cb2c019c35d5398b85adc676da4238bc7",
+            "945373f0b3431a103333ba6a0a34f1efab2702efde41754c4cb1d5216d5b0a92a67458d968562bde7fa6310a83f53dda1383680a276a283438d58ceebfa7ab7ba72499d4a3eddc860595f63c93b1c5e823ea41fc490d938398a26db28f61857698553e93f0574eb8c5017bfed6249491f9976aaa8d23d9485339cc85ca329308",
+        ]);
+    }
+
+    // The test vectors of RFC 9380, appendix K.7
+    #[test]
+    fn shake256_vectors() {
+        check(expand_message_shake256, b"QUUX-V01-CS02-with-expander-SHAKE256", [
+            "2ffc05c48ed32b95d72e807f6eab9f7530dd1c2f013914c8fed38c5ccc15ad76",
+            "b39e493867e2767216792abce1f2676c197c0692aed061560ead251821808e07",
+            "245389cf44a13f0e70af8665fe5337ec2dcd138890bb7901c4ad9cfceb054b65",
+            "719b3911821e6428a5ed9b8e600f2866bcf23c8f0515e52d6c6c019a03f16f0e",
+            "9181ead5220b1963f1b5951f35547a5ea86a820562287d6ca4723633d17ccbbc",
+            "7a1361d2d7d82d79e035b8880c5a3c86c5afa719478c007d96e6c88737a3f631dd74a2c88df79a4cb5e5d9f7504957c70d669ec6bfedc31e01e2bacc4ff3fdf9b6a00b17cc18d9d72ace7d6b81c2e481b4f73f34f9a7505dccbe8f5485f3d20c5409b0310093d5d6492dea4e18aa6979c23c8ea5de01582e9689612afbb353df",
+            "a54303e6b172909783353ab05ef08dd435a558c3197db0c132134649708e0b9b4e34fb99b92a9e9e28fc1f1d8860d85897a8e021e6382f3eea10577f968ff6df6c45fe624ce65ca25932f679a42a404bc3681efe03fcd45ef73bb3a8f79ba784f80f55ea8a3c367408f30381299617f50c8cf8fbb21d0f1e1d70b0131a7b6fbe",
+            "e42e4d9538a189316e3154b821c1bafb390f78b2f010ea404e6ac063deb8c0852fcd412e098e231e43427bd2be1330bb47b4039ad57b30ae1fc94e34993b162ff4d695e42d59d9777ea18d3848d9d336c25d2acb93adcad009bcfb9cde12286df267ada283063de0bb1505565b2eb6c90e31c48798ecdc71a71756a9110ff373",
+            "4ac054dda0a38a65d0ecf7afd3c2812300027c8789655e47aecf1ecc1a2426b17444c7482c99e5907afd9c25b991990490bb9c686f43e79b4471a23a703d4b02f23c669737a886a7ec28bddb92c3a98de63ebf878aa363a501a60055c048bea11840c4717beae7eee28c3cfa42857b3d130188571943a7bd747de831bd6444e0",
+            "09afc76d51c2cccbc129c2315df66c2be7295a231203b8ab2dd7f95c2772c68e500bc72e20c602abc9964663b7a03a389be128c56971ce81001a0b875e7fd17822db9d69792ddf6a23a151bf470079c518279aef3e75611f8f828994a9988f4a8a256ddb8bae161e658d5a2a09bcfe839c6396dc06ee5c8ff3c22d3b1f9deb7e",
+        ]);
+    }
+
+    #[test]
+    fn shake256_long_dst_vectors() {
+        check(expand_message_shake256, &long_dst(b"QUUX-V01-CS02-with-expander-SHAKE256-long-DST-"), [
+            "298dc0cf58b9c68810e45a4047f38c1eb562bcc2d31b1d2ea594e0f0ef9a2b7c",
+            "eee96d14891c97703feec48d64408db3efb3fa7d5c12bdc0932aae44e5805219",
+            "b33bfe11c6d7f8bd6f4838290fe047d9030cf81cda6c2bd7d240f1ad1ce4426f",
+            "37c3c0966cbde3cfe2bcf0dba6bf9a63d207be2a1cb77e3dfbb38f3257d8050d",
+            "33acf73896dbb6497d57bdeea5d0babca9536e9b69a6dc8b6a124748cee1ed24",
+            "60431f2ebc399d0084ab66f92969357eded4f29e1e8cf60973b738580292cf46aba5ae25a30095e2e5eb5e8a70f460a3d7b7101cb1c1d63c793716a9941d904ddf652c83d7501901f83c01c88e9522dcc6fa4b09a6ab9c89b7de5dc7e475629ec587e0cca731f61e85ef86efa0fdb8f8876ff5725ab6146b4f61ef6ab94c6e76",
+            "c74ede3f6f4c2cbd812f0de85213c3ce437eccfd99924a08c114d44f0697c08cc526ee9dfdf5bda0f19efe065ed3a010ef3012eaf2096e2b81a8f0cade6e1751940a53f533e7f342421ce51f51d69fafbbd48e00cfc528a9faa132d4b29c2bf15764f3b1469fc64e80ab20e1760b4f26926c337ac04bda9d767c58b28dbdd2c6",
+            "3514e434b8eb22c9066ab6683c03c9e82854e46c98907d00b315711ed29cb8e9099a6d1d34534b2e7f4a4f6519876f9d8a874b7433a585f6083f89417b8ace70e3eaf28542b40da182693a8437ca3a1307e0868d0e09c8bc70c2714be0d8fb0e6d37ed722dd52be8117311b600dcad18271bb815b45d8675751f66b5f35be50b",
+            "0e814137446a2e7200589ccdcf6afdcffecf96a362831baadc85ab638eb3c81fd724ee115d619d95106796b9ad30a7c9c8ba18468a3b650cf2bf4c4a3bee619ace6e5e8717eb49dd87adf9c9a7d698cb71c0e9542f28fd0f749d988b80b9e2639cf97bdfc83fd6e4526a897f0c2df2c8a71c9625f5626bc6b53701e622219360",
+            "a3e521f70205464fa94ebf9f00ce29c5ab3dcc38424b45b71d91f40d1fed8d4ad2fc30976a368415cef9bb8825e9fe30802595c9bbbfa129e3d1033c22688837c75157bce52ee44cf3cff0fa36bed786b59844afc5ff616e6a3ecb3ea25b75df476b2103d74db2fa1e01e7e296e83ed3242c12d1aa4db288b04c291ae177db88",
+        ]);
+    }
+
+    #[test]
+    fn length_limits() {
+        let mut out = [0u8;MAX_OUTPUT + 1];
+        assert_eq!(expand_message_shake128(b"msg", b"DST", &mut out[..MAX_OUTPUT]), Ok(()));
+        assert_eq!(expand_message_shake256(b"msg", b"DST", &mut out), Err(Error::OutputTooLong { requested: 65536, max: 65535 }));
+        assert_eq!(expand_message_shake256(b"msg", b"", &mut out[..32]), Err(Error::EmptyDst));
+
+        // a tag of exactly 255 bytes is used as it is, computed with Python's hashlib.shake_128
+        let mut a = [0u8;32];
+        expand_message_shake128(b"msg", &[b'x';255], &mut a).unwrap();
+        assert_eq!(a[..], from_hex("4bdb3bf781b66b49d5cedf566934330ebc3c3647a693620287aa1d5c87200704"));
+    }
+}
@@ -0,0 +1,170 @@
+//! HKDF as defined in RFC 5869, instantiated with [HMAC-SHA3-256 and HMAC-SHA3-512](crate::hmac), for deriving keys from shared secrets.
+//!
+//! [Extract](hkdf_sha3_256_extract) concentrates the entropy of the input keying material into a pseudorandom key,
+//! [expand](hkdf_sha3_256_expand) stretches that key into any number of output bytes bound to the `info` string.
+//! The output length is given by the size of the buffer and can be at most 255 times the hash length,
+//! 8160 bytes for SHA3-256 and 16320 bytes for SHA3-512.
+//! An empty salt is the same as a salt of zeros with the length of the hash
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::hkdf::{hkdf_sha3_256, hkdf_sha3_256_expand, hkdf_sha3_256_extract};
+//!
+//! let shared_secret = [0x42u8; 32];
+//! let mut session_key = [0u8; 32];
+//! hkdf_sha3_256(b"salt", &shared_secret, b"session key", &mut session_key).unwrap();
+//!
+//! // the same as both steps on their own
+//! let prk = hkdf_sha3_256_extract(b"salt", &shared_secret);
+//! let mut key = [0u8; 32];
+//! hkdf_sha3_256_expand(&prk, b"session key", &mut key).unwrap();
+//! assert_eq!(key, session_key);
+//!
+//! assert!(hkdf_sha3_256_expand(&prk, b"", &mut [0u8; 8161]).is_err());
+//! ```
+use crate::error::Error;
+use crate::hmac::Hmac;
+
+pub fn hkdf_sha3_256_extract(salt:&[u8], ikm:&[u8]) -> [u8;32] {
+    let mut prk = [0u8;32];
+    extract::<17, 256>(salt, ikm, &mut prk);
+    prk
+}
+pub fn hkdf_sha3_512_extract(salt:&[u8], ikm:&[u8]) -> [u8;64] {
+    let mut prk = [0u8;64];
+    extract::<9, 512>(salt, ikm, &mut prk);
+    prk
+}
+
+/// Fills `okm` with output keying material derived from the pseudorandom key `prk`
+///
+/// # Errors
+/// [Error::OutputTooLong] if `okm` is longer than 8160 bytes
+pub fn hkdf_sha3_256_expand(prk:&[u8], info:&[u8], okm:&mut [u8]) -> Result<(), Error> {
+    expand::<17, 256>(prk, info, okm)
+}
+
+/// Fills `okm` with output keying material derived from the pseudorandom key `prk`
+///
+/// # Errors
+/// [Error::OutputTooLong] if `okm` is longer than 16320 bytes
+pub fn hkdf_sha3_512_expand(prk:&[u8], info:&[u8], okm:&mut [u8]) -> Result<(), Error> {
+    expand::<9, 512>(prk, info, okm)
+}
+
+/// Extract followed by expand
+///
+/// # Errors
+/// [Error::OutputTooLong] if `okm` is longer than 8160 bytes
+pub fn hkdf_sha3_256(salt:&[u8], ikm:&[u8], info:&[u8], okm:&mut [u8]) -> Result<(), Error> {
+    expand::<17, 256>(&hkdf_sha3_256_extract(salt, ikm), info, okm)
+}
+
+/// Extract followed by expand
+///
+/// # Errors
+/// [Error::OutputTooLong] if `okm` is longer than 16320 bytes
+pub fn hkdf_sha3_512(salt:&[u8], ikm:&[u8], info:&[u8], okm:&mut [u8]) -> Result<(), Error> {
+    expand::<9, 512>(&hkdf_sha3_512_extract(salt, ikm), info, okm)
+}
+
+/// `PRK = HMAC(salt, IKM)`
+fn extract<const RATE:usize, const DIGEST:usize>(salt:&[u8], ikm:&[u8], prk:&mut [u8]) {
+    let mut mac = Hmac::<RATE, DIGEST>::new(salt);
+    mac.update(ikm);
+    mac.finalize_into(prk);
+}
+
+/// `T(i) = HMAC(PRK, T(i-1) || info || i)`, the output is `T(1) || T(2) || ...`
+fn expand<const RATE:usize, const DIGEST:usize>(prk:&[u8], info:&[u8], okm:&mut [u8]) -> Result<(), Error> {
+    let len = DIGEST / 8;
+    if okm.len() > 255*len {
+        return Err(Error::OutputTooLong { requested: okm.len(), max: 255*len });
+    }
+
+    let mut t = [0u8;64];
+    for (i, chunk) in okm.chunks_mut(len).enumerate() {
+        let mut mac = Hmac::<RATE, DIGEST>::new(prk);
+        if i > 0 {
+            mac.update(&t[..len]);
+        }
+        mac.update(info);
+        mac.update(&[i as u8 + 1]);
+        mac.finalize_into(&mut t[..len]);
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::from_hex;
+
+    /// The inputs of the three SHA-256 test cases of RFC 5869 as `(ikm, salt, info, length, index)`
+    fn cases(f:impl Fn(&[u8], &[u8], &[u8], usize, usize)) {
+        let a:[u8;80] = core::array::from_fn(|i| i as u8);
+        let b:[u8;80] = core::array::from_fn(|i| 0x60 + i as u8);
+        let c:[u8;80] = core::array::from_fn(|i| 0xb0 + i as u8);
+
+        f(&[0x0b;22], &a[..13], &c[0x40..0x4a], 42, 0);
+        f(&a, &b, &c, 82, 1);
+        f(&[0x0b;22], &[], &[], 42, 2);
+    }
+
+    // Computed with Python's hmac module following the steps of RFC 5869
+    #[test]
+    fn hmac_sha3_256_vectors() {
+        let expected = [
+            ("7d4194836f7a113a44677abc825640ade07af1c1d69a9a4b109b280a8fe54ef0",
+                "0c5160501d65021deaf2c14f5abce04c5bd2635abceeba61c2edb6e8ed72674900557728f2c9f2c4c179"),
+            ("addf31835b49366ac27734104d9f1865c1c2e7c8a2ebc1fed712808e4eab677c",
+                "3dc251e66c75da6560405ec5ac10e17d851eedfbfdc13feafbec16964c25d021bd971465a3e9c615f27769019e3f0407d84986fb0ba24e729c99834624baa21cb623dc0098f430d52e18bbdf694df4edd8b2"),
+            ("b899e6e4b88a35f9f5d618f48b424c313f9704012763eb6295414d673365928a",
+                "bc1342cdd75c05e8b0c3ae609ce4410684d197232875073499b30cdfe2de2853c1c1bed63d725e885e78"),
+        ];
+        cases(|ikm, salt, info, len, i| {
+            let mut out = [0u8;128];
+            assert_eq!(hkdf_sha3_256_extract(salt, ikm)[..], from_hex(expected[i].0));
+            hkdf_sha3_256(salt, ikm, info, &mut out[..len]).unwrap();
+            assert_eq!(out[..len], from_hex(expected[i].1));
+        });
+    }
+
+    #[test]
+    fn hmac_sha3_512_vectors() {
+        let expected = [
+            ("e1c543094f64f3d6c6658a94a94e3818ba13d0b3e77074b80f88f32e6b8433b703536cb500753967fae2ea977e11e4dd4f45389807cdf255b395e46807c87d5d",
+                "40e9f17e9bf2ef99425c2b23ccdf20a018ea5513f9ae68e1ea8c626deb57dfa4d56c27ccf2a2a24488a5"),
+            ("bc138b5ec5f398198e333105a8ed3c2e775016e53c8de21aaddc2d776964e14e9e1fd19bf5678aa97c2a57427d1eeac6e8ca44ddbae018a47dc18fe8201efdc6",
+                "3adf31011245f82cc6b5c3b2ea31fe2a9b855b425c3ecdd8da4a3fc5d0c3563f63bbdedf7ca912d2e98cbc853d978066ab177f19a7349e3982549b82a307e2113891691f2536ce45eb5ddf9b5175859ce8d5"),
+            ("37a48c72dce8c34bf1a08356c929133ea60a20c6c2eb3ce26d2c3ce6b0e2385572e82fc77418ace2f6df0419eacafc847fdf283b0324163d7d88265a8e7e4992",
+                "38bd71e45b397b775b563365a33258a6fd83abc1e86acf042f0723c2b68ebf073a75c34c69328835ee4c"),
+        ];
+        cases(|ikm, salt, info, len, i| {
+            let mut out = [0u8;128];
+            assert_eq!(hkdf_sha3_512_extract(salt, ikm)[..], from_hex(expected[i].0));
+            hkdf_sha3_512(salt, ikm, info, &mut out[..len]).unwrap();
+            assert_eq!(out[..len], from_hex(expected[i].1));
+        });
+    }
+
+    #[test]
+    fn output_length() {
+        let prk = hkdf_sha3_256_extract(b"", b"secret");
+        let mut okm = [0u8;16321];
+
+        assert_eq!(hkdf_sha3_256_expand(&prk, b"", &mut okm[..8160]), Ok(()));
+        assert_eq!(hkdf_sha3_256_expand(&prk, b"", &mut okm[..8161]), Err(Error::OutputTooLong { requested: 8161, max: 8160 }));
+        assert_eq!(hkdf_sha3_512_expand(&prk, b"", &mut okm[..16320]), Ok(()));
+        assert_eq!(hkdf_sha3_512(b"", b"secret", b"", &mut okm), Err(Error::OutputTooLong { requested: 16321, max: 16320 }));
+
+        // shorter outputs are prefixes of longer ones
+        let mut short = [0u8;50];
+        hkdf_sha3_256_expand(&prk, b"info", &mut short).unwrap();
+        hkdf_sha3_256_expand(&prk, b"info", &mut okm[..100]).unwrap();
+        assert_eq!(short, okm[..50]);
+
+        hkdf_sha3_256_expand(&prk, b"info", &mut []).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+//! HMAC over the SHA-3 functions as defined in FIPS 198-1, for protocols that need HMAC rather than [KMAC](crate::kmac).
+//!
+//! The key is padded to the block size of the hash function, which for SHA-3 is its rate:
+//! 144, 136, 104 and 72 bytes for SHA3-224, SHA3-256, SHA3-384 and SHA3-512. Keys longer than that are hashed first.
+//! The tag is `H((K ^ opad) || H((K ^ ipad) || m))`
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use jisp_sha3::hmac::{hmac_sha3_256, HmacSha3_256};
+//!
+//! let tag = hmac_sha3_256(b"key", b"message");
+//!
+//! let mut mac = HmacSha3_256::new(b"key");
+//! mac.update(b"mess");
+//! mac.update(b"age");
+//! assert!(mac.verify(&tag));
+//! # }
+//! ```
+use crate::internals::verify::constant_time_eq;
+use crate::sha3::Sha3;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+const IPAD:u8 = 0x36;
+const OPAD:u8 = 0x5c;
+
+#[cfg(feature = "alloc")]
+pub fn hmac_sha3_224(key:&[u8], m:&[u8]) -> Vec<u8> {
+    hmac::<18, 224>(key, m)
+}
+#[cfg(feature = "alloc")]
+pub fn hmac_sha3_256(key:&[u8], m:&[u8]) -> Vec<u8> {
+    hmac::<17, 256>(key, m)
+}
+#[cfg(feature = "alloc")]
+pub fn hmac_sha3_384(key:&[u8], m:&[u8]) -> Vec<u8> {
+    hmac::<13, 384>(key, m)
+}
+#[cfg(feature = "alloc")]
+pub fn hmac_sha3_512(key:&[u8], m:&[u8]) -> Vec<u8> {
+    hmac::<9, 512>(key, m)
+}
+
+#[cfg(feature = "alloc")]
+fn hmac<const RATE:usize, const DIGEST:usize>(key:&[u8], m:&[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<RATE, DIGEST>::new(key);
+    mac.update(m);
+    mac.finalize()
+}
+
+/// Incremental version of HMAC, use one of the aliases [HmacSha3_224], [HmacSha3_256], [HmacSha3_384] or [HmacSha3_512]
+#[derive(Debug, Clone)]
+pub struct Hmac<const RATE:usize, const DIGEST:usize> {
+    inner:Sha3<RATE, DIGEST>,
+    outer:Sha3<RATE, DIGEST>,
+}
+
+pub type HmacSha3_224 = Hmac<18, 224>;
+pub type HmacSha3_256 = Hmac<17, 256>;
+pub type HmacSha3_384 = Hmac<13, 384>;
+pub type HmacSha3_512 = Hmac<9, 512>;
+
+impl<const RATE:usize, const DIGEST:usize> Hmac<RATE, DIGEST> {
+    /// Keys of any length are allowed, keys longer than the block size are replaced by their hash
+    pub fn new(key:&[u8]) -> Self {
+        let block = RATE*8;
+
+        //K0, the key padded with zeros to the block size
+        let mut k0 = [0u8;200];
+        if key.len() > block {
+            let mut hasher = Sha3::<RATE, DIGEST>::new();
+            hasher.update(key);
+            hasher.finalize_into(&mut k0[..DIGEST / 8]);
+        } else {
+            k0[..key.len()].copy_from_slice(key);
+        }
+
+        let mut pad = [0u8;200];
+        let mut inner = Sha3::new();
+        for i in 0..block {
+            pad[i] = k0[i] ^ IPAD;
+        }
+        inner.update(&pad[..block]);
+
+        let mut outer = Sha3::new();
+        for i in 0..block {
+            pad[i] = k0[i] ^ OPAD;
+        }
+        outer.update(&pad[..block]);
+
+        Hmac { inner, outer }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        self.inner.update(m);
+    }
+
+    /// Finishes the message and returns the `DIGEST` bit tag
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self) -> Vec<u8> {
+        let mut res = vec![0u8; DIGEST / 8];
+        self.finalize_into(&mut res);
+        res
+    }
+
+    /// Finishes the message and writes the `DIGEST` bit tag to `out`, without allocating
+    ///
+    /// # Panics
+    /// If `out` is not `DIGEST / 8` bytes long
+    pub fn finalize_into(mut self, out:&mut [u8]) {
+        if out.len() != DIGEST / 8 {panic!("A buffer of {} bytes does not fit a tag of {} bits!", out.len(), DIGEST)}
+        let mut inner_hash = [0u8;64];
+        self.inner.finalize_into(&mut inner_hash[..DIGEST / 8]);
+        self.outer.update(&inner_hash[..DIGEST / 8]);
+        self.outer.finalize_into(out);
+    }
+
+    /// Checks whether `tag` is the HMAC of the message, the tags are compared in constant time.
+    /// Only full length tags are accepted
+    pub fn verify(self, tag:&[u8]) -> bool {
+        let mut expected = [0u8;64];
+        self.finalize_into(&mut expected[..DIGEST / 8]);
+        constant_time_eq(&expected[..DIGEST / 8], tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::from_hex;
+    use std::{vec, vec::Vec};
+
+    fn tag<const RATE:usize, const DIGEST:usize>(key:&[u8], m:&[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<RATE, DIGEST>::new(key);
+        mac.update(m);
+        let mut res = vec![0u8; DIGEST / 8];
+        mac.finalize_into(&mut res);
+        res
+    }
+
+    fn key() -> [u8;200] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    // The samples published by NIST for HMAC-SHA3, with keys shorter than, equal to and longer than the block size
+    #[test]
+    fn nist_samples() {
+        let short = b"Sample message for keylen<blocklen";
+        let equal = b"Sample message for keylen=blocklen";
+        let long = b"Sample message for keylen>blocklen";
+
+        assert_eq!(tag::<18, 224>(&key()[..28], short), from_hex("332cfd59347fdb8e576e77260be4aba2d6dc53117b3bfb52c6d18c04"));
+        assert_eq!(tag::<18, 224>(&key()[..144], equal), from_hex("d8b733bcf66c644a12323d564e24dcf3fc75f231f3b67968359100c7"));
+        assert_eq!(tag::<18, 224>(&key()[..172], long), from_hex("078695eecc227c636ad31d063a15dd05a7e819a66ec6d8de1e193e59"));
+
+        assert_eq!(tag::<17, 256>(&key()[..32], short), from_hex("4fe8e202c4f058e8dddc23d8c34e467343e23555e24fc2f025d598f558f67205"));
+        assert_eq!(tag::<17, 256>(&key()[..136], equal), from_hex("68b94e2e538a9be4103bebb5aa016d47961d4d1aa906061313b557f8af2c3faa"));
+        assert_eq!(tag::<17, 256>(&key()[..168], long), from_hex("9bcf2c238e235c3ce88404e813bd2f3a97185ac6f238c63d6229a00b07974258"));
+
+        assert_eq!(tag::<13, 384>(&key()[..48], short),
+            from_hex("d588a3c51f3f2d906e8298c1199aa8ff6296218127f6b38a90b6afe2c5617725bc99987f79b22a557b6520db710b7f42"));
+        assert_eq!(tag::<13, 384>(&key()[..104], equal),
+            from_hex("a27d24b592e8c8cbf6d4ce6fc5bf62d8fc98bf2d486640d9eb8099e24047837f5f3bffbe92dcce90b4ed5b1e7e44fa90"));
+        assert_eq!(tag::<13, 384>(&key()[..152], long),
+            from_hex("e5ae4c739f455279368ebf36d4f5354c95aa184c899d3870e460ebc288ef1f9470053f73f7c6da2a71bcaec38ce7d6ac"));
+
+        assert_eq!(tag::<9, 512>(&key()[..64], short),
+            from_hex("4efd629d6c71bf86162658f29943b1c308ce27cdfa6db0d9c3ce81763f9cbce5f7ebe9868031db1a8f8eb7b6b95e5c5e3f657a8996c86a2f6527e307f0213196"));
+        assert_eq!(tag::<9, 512>(&key()[..72], equal),
+            from_hex("544e257ea2a3e5ea19a590e6a24b724ce6327757723fe2751b75bf007d80f6b360744bf1b7a88ea585f9765b47911976d3191cf83c039f5ffab0d29cc9d9b6da"));
+        assert_eq!(tag::<9, 512>(&key()[..136], long),
+            from_hex("5f464f5e5b7848e3885e49b2c385f0694985d0e38966242dc4a5fe3fea4b37d46b65ceced5dcf59438dd840bab22269f0ba7febdb9fcf74602a35666b2a32915"));
+    }
+
+    #[test]
+    fn long_key_is_hashed() {
+        let long = key();
+        let mut hashed = [0u8;32];
+        let mut hasher = Sha3::<17, 256>::new();
+        hasher.update(&long);
+        hasher.finalize_into(&mut hashed);
+        assert_eq!(tag::<17, 256>(&long, b"m"), tag::<17, 256>(&hashed, b"m"));
+    }
+
+    #[test]
+    fn verification() {
+        let m:[u8;300] = core::array::from_fn(|i| i as u8);
+        let expected = tag::<13, 384>(b"key", &m);
+
+        let mut mac = HmacSha3_384::new(b"key");
+        for part in m.chunks(70) {
+            mac.update(part);
+        }
+        assert!(mac.clone().verify(&expected));
+        assert!(!mac.clone().verify(&expected[..47]));
+
+        let mut wrong = expected;
+        wrong[47] ^= 1;
+        assert!(!mac.verify(&wrong));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn one_shot() {
+        assert_eq!(hmac_sha3_224(b"key", b"abc"), tag::<18, 224>(b"key", b"abc"));
+        assert_eq!(hmac_sha3_256(b"key", b"abc"), tag::<17, 256>(b"key", b"abc"));
+        assert_eq!(hmac_sha3_384(b"key", b"abc"), tag::<13, 384>(b"key", b"abc"));
+        assert_eq!(hmac_sha3_512(b"key", b"abc"), tag::<9, 512>(b"key", b"abc"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_output_length() {
+        HmacSha3_256::new(b"key").finalize_into(&mut [0u8;31]);
+    }
+}
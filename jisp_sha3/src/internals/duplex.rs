@@ -0,0 +1,226 @@
+//! The duplex construction from "Duplexing the sponge" by Bertoni, Daemen, Peeters and Van Assche.
+//!
+//! Unlike the [Sponge](crate::internals::sponge::Sponge) a duplex object keeps its state between calls:
+//! every call to [duplexing](Duplex::duplexing) pads and absorbs one input block and immediately returns output that depends on all earlier inputs.
+//! This makes it a building block for protocols and authenticated encryption such as [SpongeWrap](crate::spongewrap)
+use crate::internals::keccak::keccak_f1600;
+use crate::preprocessing::{bytes_to_lane, lane_to_bytes};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+/// A duplex object with a rate of `RATE` words on Keccak-f\[1600\].
+/// Every input is followed by the suffix and pad10*1, so one call to [duplexing](Duplex::duplexing) on a new object
+/// gives the same output as the sponge with that suffix
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use jisp_sha3::internals::duplex::Duplex;
+/// use jisp_sha3::sha3::sha3_256;
+///
+/// let mut duplex = Duplex::<17>::new(&[false, true]);
+/// assert_eq!(duplex.duplexing(b"abc", 256), sha3_256(b"abc"));
+///
+/// // the next output depends on both inputs
+/// let z = duplex.duplexing(b"def", 128);
+/// assert_eq!(z.len(), 16);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Duplex<const RATE:usize> {
+    state:[u64;25],
+    /// The bits of the suffix, starting at the least significant bit
+    suffix:u64,
+    suffix_len:usize,
+    rounds:i64,
+}
+
+impl<const RATE:usize> Duplex<RATE> {
+    /// # Panics
+    /// If the suffix is longer than 64 bits or does not fit in a block together with the padding
+    pub fn new(suffix:&[bool]) -> Self {
+        Self::with_rounds(suffix, 24)
+    }
+
+    /// A duplex object that uses the last `rounds` rounds of the keccak permutation instead of all 24
+    ///
+    /// # Panics
+    /// If the suffix is longer than 64 bits or does not fit in a block together with the padding
+    pub fn with_rounds(suffix:&[bool], rounds:i64) -> Self {
+        let mut duplex = Duplex { state: [0;25], suffix: 0, suffix_len: 0, rounds };
+        duplex.set_suffix(suffix);
+        duplex
+    }
+
+    /// Changes the suffix that is added to the inputs of the next calls,
+    /// for example to add a frame bit that tells different kinds of blocks apart
+    ///
+    /// # Panics
+    /// If the suffix is longer than 64 bits or does not fit in a block together with the padding
+    pub fn set_suffix(&mut self, suffix:&[bool]) {
+        if suffix.len() > 64 {panic!("A suffix of {} bits is too long, it can be at most 64 bits!", suffix.len())}
+        if suffix.len() + 2 > RATE*64 {panic!("A suffix of {} bits does not fit in a block of {} bits with the padding!", suffix.len(), RATE*64)}
+        self.suffix = 0;
+        for (i, bit) in suffix.iter().enumerate() {
+            self.suffix |= (*bit as u64) << i;
+        }
+        self.suffix_len = suffix.len();
+    }
+
+    /// The maximum number of input bytes in a single call, such that the input, the suffix and the padding fit in one block
+    pub fn max_input(&self) -> usize {
+        (RATE*64 - self.suffix_len - 2) / 8
+    }
+
+    /// Absorbs one block of input and returns `output` bits, which can be at most the rate.
+    /// Like [squeeze](crate::internals::sponge::Sponge::squeeze) the output is rounded up to whole bytes
+    ///
+    /// # Panics
+    /// If the input is longer than [max_input](Duplex::max_input) or the output longer than the rate
+    #[cfg(feature = "alloc")]
+    pub fn duplexing(&mut self, input:&[u8], output:usize) -> Vec<u8> {
+        let mut res = vec![0u8; output.div_ceil(8)];
+        self.duplexing_into(input, &mut res);
+        res
+    }
+
+    /// Absorbs one block of input and fills `output` with the first bytes of the new state, without allocating
+    ///
+    /// # Panics
+    /// If the input is longer than [max_input](Duplex::max_input) or the output longer than the rate
+    pub fn duplexing_into(&mut self, input:&[u8], output:&mut [u8]) {
+        if input.len() > self.max_input() {panic!("An input of {} bytes does not fit in a block, the maximum is {}!", input.len(), self.max_input())}
+        if output.len() > RATE*8 {panic!("An output of {} bytes is longer than the rate of {} bytes!", output.len(), RATE*8)}
+
+        let mut block = [0u8;200];
+        block[..input.len()].copy_from_slice(input);
+
+        //the suffix and pad10*1
+        for i in 0..=self.suffix_len {
+            if i == self.suffix_len || (self.suffix >> i) & 1 == 1 {
+                let bit = input.len()*8 + i;
+                block[bit / 8] |= 1 << (bit % 8);
+            }
+        }
+        block[RATE*8 - 1] |= 0x80;
+
+        for i in 0..RATE {
+            self.state[i] ^= bytes_to_lane(block[8*i..8*i + 8].try_into().unwrap());
+        }
+        keccak_f1600(&mut self.state, self.rounds);
+
+        for (i, byte) in output.iter_mut().enumerate() {
+            *byte = lane_to_bytes(self.state[i / 8])[i % 8];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn single_call_is_sponge() {
+        use crate::internals::sponge::{keccak_c, Sponge};
+
+        let m:Vec<u8> = (0..200).map(|i| i as u8).collect();
+        for len in [0, 1, 100, 134, 135] {
+            let mut duplex = Duplex::<17>::new(&[false, true]);
+            assert_eq!(duplex.duplexing(&m[..len], 1088), keccak_c::<17>(&m[..len], &[false, true], 1088));
+        }
+
+        let mut duplex = Duplex::<21>::new(&[true;4]);
+        assert_eq!(duplex.duplexing(&m[..167], 1000), keccak_c::<21>(&m[..167], &[true;4], 1000));
+
+        // with fewer rounds
+        let mut duplex = Duplex::<21>::with_rounds(&[true, true], 12);
+        let mut sponge = Sponge::<21>::with_rounds(&[true, true], 12);
+        sponge.absorb(&m[..50]);
+        assert_eq!(duplex.duplexing(&m[..50], 512), sponge.squeeze(512));
+    }
+
+    // Every output of the duplex object is the output of the sponge on all padded inputs so far
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn outputs_follow_the_sponge() {
+        use crate::internals::sponge::keccak_c_bits;
+        use crate::preprocessing::BitString;
+
+        let suffix = vec![true, false];
+        let first:Vec<u8> = (0..70).collect();
+        let second = b"second block";
+
+        let mut duplex = Duplex::<9>::new(&suffix);
+        duplex.duplexing(&first, 0);
+        let z = duplex.duplexing(second, 400);
+
+        let mut m = BitString::from_bytes(&first, first.len()*8);
+        for bit in &suffix {
+            m.push(*bit);
+        }
+        m.push(true);
+        while !(m.len() + 1).is_multiple_of(576) {
+            m.push(false);
+        }
+        m.push(true);
+        for byte in second {
+            for i in 0..8 {
+                m.push((byte >> i) & 1 == 1);
+            }
+        }
+        assert_eq!(z, keccak_c_bits::<9>(&m, &suffix, 400).as_bytes());
+    }
+
+    #[test]
+    fn changing_suffix() {
+        let mut a = Duplex::<17>::new(&[]);
+        let mut b = Duplex::<17>::new(&[true]);
+        b.set_suffix(&[]);
+
+        let (mut za, mut zb) = ([0u8; 32], [0u8; 32]);
+        a.duplexing_into(b"abc", &mut za);
+        b.duplexing_into(b"abc", &mut zb);
+        assert_eq!(za, zb);
+
+        a.set_suffix(&[true]);
+        a.duplexing_into(b"", &mut za);
+        b.duplexing_into(b"", &mut zb);
+        assert_ne!(za, zb);
+    }
+
+    #[test]
+    fn maximum_input() {
+        assert_eq!(Duplex::<17>::new(&[false, true]).max_input(), 135);
+        assert_eq!(Duplex::<17>::new(&[true; 7]).max_input(), 134);
+    }
+
+    #[test]
+    fn suffix_fills_small_rate() {
+        assert_eq!(Duplex::<1>::new(&[true; 62]).max_input(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn suffix_too_long_for_rate() {
+        Duplex::<1>::new(&[true; 63]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_suffix_too_long_for_rate() {
+        Duplex::<1>::new(&[]).set_suffix(&[false; 64]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn input_too_long() {
+        Duplex::<17>::new(&[]).duplexing_into(&[0; 136], &mut []);
+    }
+
+    #[test]
+    #[should_panic]
+    fn output_too_long() {
+        Duplex::<17>::new(&[]).duplexing_into(&[], &mut [0; 137]);
+    }
+}
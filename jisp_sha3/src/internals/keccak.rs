@@ -1,41 +1,295 @@
-//! The internals of the Keccak permutation function
+//! The internals of the Keccak permutation function. 
+//! The step mappings work on every width of the permutation Keccak-f\[25W\], from lanes of 1 bit up to the 64 bit lanes used in SHA-3
 #![allow(non_snake_case)]
-use std::ops::BitXor;
 
-use crate::internals::state::{State, Sheet, to_state, from_state, Modulus};
+use crate::internals::state::{State, Sheet, Lane, Modulus};
 
+/// Performs the last `rounds` rounds of Keccak-f\[1600\] on a state of 25 words
+#[allow(clippy::needless_return)]
 pub fn keccak(str_state:[u64;25], rounds:i64) -> [u64;25] {
-    let mut state = to_state(&str_state.to_vec());
-    let n = 24;
+    let mut state = str_state;
+    keccak_f1600(&mut state, rounds);
+    return state;
+}
+
+/// The round constants of Keccak-f\[1600\] as given by [rc], in the usual notation where bit `z` of a lane is bit `2^z` of the word
+const STANDARD_RC:[u64;24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000, 
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009, 
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a, 
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003, 
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a, 
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// The round constants in the lane ordering of this crate, where bit `z` of a lane is bit `2^(63-z)` of the word
+const ROUND_CONSTANTS:[u64;24] = {
+    let mut res = [0;24];
+    let mut i = 0;
+    while i < 24 {
+        res[i] = STANDARD_RC[i].reverse_bits();
+        i += 1;
+    }
+    res
+};
+
+/// The offsets by which [rho] rotates every lane, indexed by `x + 5y`
+const ROTATION_OFFSETS:[u32;25] = [
+     0,  1, 62, 28, 27, 
+    36, 44,  6, 55, 20, 
+     3, 10, 43, 25, 39, 
+    41, 45, 15, 21,  8, 
+    18,  2, 61, 56, 14,
+];
+
+/// Performs the last `rounds` rounds of Keccak-f\[1600\] in place, working on whole lanes at a time. 
+/// Gives the same result as the step mappings below, but much faster. 
+/// Since bit 0 of a lane is the most significant bit of its word, rotating a lane means rotating its word to the right
+///
+/// # Panics
+/// If `rounds` is more than 24
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::internals::keccak::{keccak_f1600, keccak_p};
+///
+/// let mut state = [0u64;25];
+/// state[3] = 0x0123456789abcdef;
+/// let expected = keccak_p::<64>(state, 24);
+///
+/// keccak_f1600(&mut state, 24);
+/// assert_eq!(state, expected);
+/// ```
+pub fn keccak_f1600(state:&mut [u64;25], rounds:i64) {
+    if rounds > 24 {panic!("Keccak-f[1600] only has 24 rounds!")}
+
+    for i in (24 - rounds)..24 {
+        //theta
+        let mut C = [0u64;5];
+        for x in 0..5 {
+            C[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let D = C[(x + 4) % 5] ^ C[(x + 1) % 5].rotate_right(1);
+            for y in 0..5 {
+                state[x + 5*y] ^= D;
+            }
+        }
+
+        //rho and pi, the lane at (x, y) moves to (y, 2x + 3y)
+        let mut B = [0u64;25];
+        for x in 0..5 {
+            for y in 0..5 {
+                B[y + 5*((2*x + 3*y) % 5)] = state[x + 5*y].rotate_right(ROTATION_OFFSETS[x + 5*y]);
+            }
+        }
+
+        //chi
+        for y in 0..5 {
+            for x in 0..5 {
+                state[x + 5*y] = B[x + 5*y] ^ (!B[(x + 1) % 5 + 5*y] & B[(x + 2) % 5 + 5*y]);
+            }
+        }
+
+        //iota
+        state[0] ^= ROUND_CONSTANTS[i as usize];
+    }
+}
+
+/// [keccak_f1600] as a `const fn`, so it can be evaluated at compile time. 
+/// Loops are written as `while` loops and the state is passed by value, since that is what a `const fn` allows
+///
+/// # Panics
+/// If `rounds` is more than 24
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::internals::keccak::{keccak_f1600, keccak_f1600_const};
+///
+/// const STATE:[u64;25] = keccak_f1600_const([0;25], 24);
+///
+/// let mut state = [0u64;25];
+/// keccak_f1600(&mut state, 24);
+/// assert_eq!(STATE, state);
+/// ```
+pub const fn keccak_f1600_const(state:[u64;25], rounds:i64) -> [u64;25] {
+    if rounds > 24 {panic!("Keccak-f[1600] only has 24 rounds!")}
+    let mut A = state;
+
+    let mut i = 24 - rounds;
+    while i < 24 {
+        //theta
+        let mut C = [0u64;5];
+        let mut x = 0;
+        while x < 5 {
+            C[x] = A[x] ^ A[x + 5] ^ A[x + 10] ^ A[x + 15] ^ A[x + 20];
+            x += 1;
+        }
+        let mut x = 0;
+        while x < 5 {
+            let D = C[(x + 4) % 5] ^ C[(x + 1) % 5].rotate_right(1);
+            let mut y = 0;
+            while y < 5 {
+                A[x + 5*y] ^= D;
+                y += 1;
+            }
+            x += 1;
+        }
+
+        //rho and pi
+        let mut B = [0u64;25];
+        let mut j = 0;
+        while j < 25 {
+            let (x, y) = (j % 5, j / 5);
+            B[y + 5*((2*x + 3*y) % 5)] = A[j].rotate_right(ROTATION_OFFSETS[j]);
+            j += 1;
+        }
+
+        //chi
+        let mut j = 0;
+        while j < 25 {
+            let (x, y) = (j % 5, j / 5);
+            A[j] = B[j] ^ (!B[(x + 1) % 5 + 5*y] & B[(x + 2) % 5 + 5*y]);
+            j += 1;
+        }
+
+        //iota
+        A[0] ^= ROUND_CONSTANTS[i as usize];
+        i += 1;
+    }
+    A
+}
+
+/// Performs the last `rounds` rounds of Keccak-f\[1600\] on `N` independent states at once. 
+/// The states are interleaved, `states[i][k]` is lane `i` of state `k`, so every step works on `N` words at a time 
+/// in loops the compiler can turn into SIMD instructions. Each state ends up the same as it would with [keccak_f1600]
+///
+/// # Panics
+/// If `rounds` is more than 24
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::internals::keccak::{keccak_f1600, keccak_f1600_many};
+///
+/// let mut states = [[0u64;4];25];
+/// states[0] = [1, 2, 3, 4];
+/// keccak_f1600_many(&mut states, 24);
+///
+/// let mut single = [0u64;25];
+/// single[0] = 3;
+/// keccak_f1600(&mut single, 24);
+///
+/// assert_eq!(states.map(|lanes| lanes[2]), single);
+/// ```
+pub fn keccak_f1600_many<const N:usize>(states:&mut [[u64;N];25], rounds:i64) {
+    if rounds > 24 {panic!("Keccak-f[1600] only has 24 rounds!")}
+
+    for i in (24 - rounds)..24 {
+        //theta
+        let mut C = [[0u64;N];5];
+        for x in 0..5 {
+            for k in 0..N {
+                C[x][k] = states[x][k] ^ states[x + 5][k] ^ states[x + 10][k] ^ states[x + 15][k] ^ states[x + 20][k];
+            }
+        }
+        for x in 0..5 {
+            let mut D = [0u64;N];
+            for k in 0..N {
+                D[k] = C[(x + 4) % 5][k] ^ C[(x + 1) % 5][k].rotate_right(1);
+            }
+            for y in 0..5 {
+                for k in 0..N {
+                    states[x + 5*y][k] ^= D[k];
+                }
+            }
+        }
+
+        //rho and pi
+        let mut B = [[0u64;N];25];
+        for x in 0..5 {
+            for y in 0..5 {
+                let offset = ROTATION_OFFSETS[x + 5*y];
+                for k in 0..N {
+                    B[y + 5*((2*x + 3*y) % 5)][k] = states[x + 5*y][k].rotate_right(offset);
+                }
+            }
+        }
+
+        //chi
+        for y in 0..5 {
+            for x in 0..5 {
+                for k in 0..N {
+                    states[x + 5*y][k] = B[x + 5*y][k] ^ (!B[(x + 1) % 5 + 5*y][k] & B[(x + 2) % 5 + 5*y][k]);
+                }
+            }
+        }
+
+        //iota
+        for lane in &mut states[0] {
+            *lane ^= ROUND_CONSTANTS[i as usize];
+        }
+    }
+}
+
+/// Performs the last `rounds` rounds of Keccak-f\[25W\], which has `12 + 2l` rounds in total for `W = 2^l`. 
+/// Each word of the state holds one lane in its `W` least significant bits. 
+/// This goes through the step mappings one bit at a time, use [keccak_f1600] for speed
+///
+/// # Panics
+/// If `W` is not one of 1, 2, 4, 8, 16, 32 or 64, or if `rounds` is more than the total number of rounds
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::internals::keccak::{keccak_p, total_rounds};
+///
+/// let state = keccak_p::<8>([0;25], total_rounds::<8>());
+/// assert_eq!(state[0], 0x3c);
+/// ```
+pub fn keccak_p<const W:usize>(str_state:[u64;25], rounds:i64) -> [u64;25] {
+    let n = total_rounds::<W>();
+    if rounds > n {panic!("Keccak-f[{}] only has {} rounds!", 25*W, n)}
+
+    let mut state = State::<W>::from_words(&str_state);
     for i in (n - rounds)..n {
         state = round(state, i);
     }
 
-    return from_state(&state);
+    state.to_words()
 }
 
-pub fn round(state:State, round_index:i64 ) -> State {
+/// The number of rounds `12 + 2l` of Keccak-f\[25W\], where `W = 2^l`
+///
+/// # Panics
+/// If `W` is not one of 1, 2, 4, 8, 16, 32 or 64
+pub fn total_rounds<const W:usize>() -> i64 {
+    if !W.is_power_of_two() || W > 64 {panic!("{} is not a valid lane width!", W)}
+    12 + 2*W.trailing_zeros() as i64
+}
+
+#[allow(clippy::needless_return)]
+pub fn round<const W:usize>(state:State<W>, round_index:i64 ) -> State<W> {
     return iota(chi(pi(rho(theta(state)))),round_index);
 }
 
 
-pub fn theta(state: State) -> State {
+#[allow(clippy::needless_return)]
+pub fn theta<const W:usize>(state: State<W>) -> State<W> {
     //we are abusing notation here since sheets usually have a constant x while they now have a constant y
-    let mut C = Sheet::default();
-    let mut D = Sheet::default();
-    let mut A = State::default();
+    let mut C = Sheet::<W>::default();
+    let mut D = Sheet::<W>::default();
+    let mut A = State::<W>::default();
     
     // Fill C
     for x in 0..5 {
-        for z in 0..64 {
-            let sum = xor_sum(&(0..5).map(|i| state[x][i].get(z)).collect());
+        for z in 0..W as i64 {
+            let sum = xor_sum((0..5).map(|i| state[x][i].get(z)));
             C[x].set(z,sum);
         }
     }
 
     // Fill D
     for x in 0..5 {
-        for z in 0..64 {
+        for z in 0..W as i64 {
             let a = C[x - 1].get(z);
             let b = C[x + 1].get(z -1);
 
@@ -46,7 +300,7 @@ pub fn theta(state: State) -> State {
     // Fill Result
     for x in 0..5 {
         for y in 0..5 {
-            for z in 0..64 {
+            for z in 0..W as i64 {
                 let a_xyz = state[x][y].get(z) ^ D[x].get(z);
                 A[x][y].set(z, a_xyz);
             }
@@ -55,14 +309,15 @@ pub fn theta(state: State) -> State {
     return A;
 }
 
-pub fn rho(state:State) -> State {
-    let mut A = State::default();
+#[allow(clippy::needless_return)]
+pub fn rho<const W:usize>(state:State<W>) -> State<W> {
+    let mut A = State::<W>::default();
     A[0][0] = state[0][0];
 
     let (mut x, mut y) = (1, 0);
     for t in 0..=23 {
-        let shift = (((t + 1) * (t + 2))/2).md(64);
-        for z in 0..64 {
+        let shift = (((t + 1) * (t + 2))/2).md(W);
+        for z in 0..W as i64 {
             let modz:i64 = z - shift;
             A[x][y].set(z, state[x][y].get(modz));
         }
@@ -71,11 +326,12 @@ pub fn rho(state:State) -> State {
     return A;
 }
 
-pub fn pi(state:State) -> State {
-    let mut A = State::default();
+#[allow(clippy::needless_return)]
+pub fn pi<const W:usize>(state:State<W>) -> State<W> {
+    let mut A = State::<W>::default();
     for x in 0..5 {
         for y in 0..5 {
-            for z in 0..64 {
+            for z in 0..W as i64 {
                 A[x][y].set(z, state[x + y*3][x].get(z));
             }
         }
@@ -83,11 +339,12 @@ pub fn pi(state:State) -> State {
     return A;
 }
 
-pub fn chi(state:State) -> State {
-    let mut A = State::default();
+#[allow(clippy::needless_return)]
+pub fn chi<const W:usize>(state:State<W>) -> State<W> {
+    let mut A = State::<W>::default();
     for x in 0..5 {
         for y in 0..5 {
-            let bit = (state[x + 1][y].0 ^u64::MAX) & state[x + 2][y].0;
+            let bit = (state[x + 1][y].0 ^Lane::<W>::MASK) & state[x + 2][y].0;
             let bit = state[x][y].0 ^bit;
             A[x][y].0 = bit;
         }
@@ -95,15 +352,17 @@ pub fn chi(state:State) -> State {
     return A;
 }
 
-pub fn iota(state:State, round_index:i64) -> State {
+#[allow(clippy::needless_return)]
+pub fn iota<const W:usize>(state:State<W>, round_index:i64) -> State<W> {
     let mut A = state;
     let mut RC = [0;64];
+    let l = W.trailing_zeros() as i64;
 
-    for j in 0..=6 {
+    for j in 0..=l {
         RC[(1 << j) - 1] = rc(j + 7*round_index);
     }
 
-    for z in 0..64 {
+    for z in 0..W as i64 {
         let (x,y) = (0, 0);
         let res = A[x][y].get(z) ^ RC[z as usize];
         A[x][y].set(z, res);
@@ -112,6 +371,7 @@ pub fn iota(state:State, round_index:i64) -> State {
     return A
 }
 
+#[allow(clippy::assign_op_pattern, clippy::needless_return)]
 pub fn rc(t:i64) -> u8 {
     let mut R = 1;
 
@@ -136,14 +396,16 @@ pub fn rc(t:i64) -> u8 {
 
 //utility functions
 
-fn xor_sum(v: &Vec<u8>) -> u8 {
+#[allow(clippy::needless_return)]
+fn xor_sum(v: impl Iterator<Item = u8>) -> u8 {
     let mut res = 0;
     for bit in v {
-        res = res.bitxor(bit);
+        res ^= bit;
     }
     return res;
 }
 
+#[allow(clippy::needless_return)]
 fn modulus(x:i64, m:u8) -> u8 {
     let m = m as i64;
     let mut x = x;
@@ -168,4 +430,133 @@ mod tests {
         let result = rc(1);
         assert_eq!(result, 0);
     }
+
+    /// Reverses the bits of a lane, the reference outputs store bit 0 of a lane in the least significant bit
+    fn reference_lanes<const W:usize>(lanes:[u64;25]) -> [u64;25] {
+        lanes.map(|lane| lane.reverse_bits() >> (64 - W))
+    }
+
+    // The permutation of the all zero state, as in the intermediate values published by the Keccak team
+    #[test]
+    fn zero_state_all_widths() {
+        let f1600 = [
+            0xf1258f7940e1dde7, 0x84d5ccf933c0478a, 0xd598261ea65aa9ee, 0xbd1547306f80494d, 0x8b284e056253d057, 
+            0xff97a42d7f8e6fd4, 0x90fee5a0a44647c4, 0x8c5bda0cd6192e76, 0xad30a6f71b19059c, 0x30935ab7d08ffc64, 
+            0xeb5aa93f2317d635, 0xa9a6e6260d712103, 0x81a57c16dbcf555f, 0x43b831cd0347c826, 0x01f22f1a11a5569f, 
+            0x05e5635a21d9ae61, 0x64befef28cc970f2, 0x613670957bc46611, 0xb87c5a554fd00ecb, 0x8c3ee88a1ccf32c8, 
+            0x940c7922ae3a2614, 0x1841f924a2c509e4, 0x16f53526e70465c2, 0x75f644e97f30a13b, 0xeaf1ff7b5ceca249];
+        let f800 = [
+            0xe531d45d, 0xf404c6fb, 0x23a0bf99, 0xf1f8452f, 0x51ffd042, 0xe539f578, 0xf00b80a7, 0xaf973664, 0xbf5af34c, 
+            0x227a2424, 0x88172715, 0x9f685884, 0xb15cd054, 0x1bf4fc0e, 0x6166fa91, 0x1a9e599a, 0xa3970a1f, 0xab659687, 
+            0xafab8d68, 0xe74b1015, 0x34001a98, 0x4119eff3, 0x930a0e76, 0x87b28070, 0x11efe996];
+        let f400 = [
+            0x09f5, 0x40ac, 0x0fa9, 0x14f5, 0xe89f, 0xeca0, 0x5bd1, 0x7870, 0xeff0, 0xbf8f, 0x0337, 0x6052, 0xdc75, 
+            0x0ec9, 0xe776, 0x5246, 0x59a1, 0x5d81, 0x6d95, 0x6e14, 0x633e, 0x58ee, 0x71ff, 0x714c, 0xb38e];
+        let f200 = [
+            0x3c, 0x28, 0x26, 0x84, 0x1c, 0xb3, 0x5c, 0x17, 0x1e, 0xaa, 0xe9, 0xb8, 0x11, 0x13, 0x4c, 0xea, 0xa3, 0x85, 
+            0x2c, 0x69, 0xd2, 0xc5, 0xab, 0xaf, 0xea];
+        let f100 = [0x6, 0x6, 0x5, 0xc, 0xd, 0xe, 0xb, 0xa, 0xd, 0x6, 0x2, 0xf, 0x0, 0x2, 0x8, 0x5, 0x0, 0xd, 0x7, 0x7, 0xe, 0xa, 0xa, 0x0, 0x1];
+        let f50 = [0, 2, 3, 1, 1, 1, 0, 3, 3, 3, 1, 1, 1, 3, 1, 0, 1, 3, 2, 0, 3, 0, 1, 0, 2];
+        let f25 = [0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 0];
+
+        assert_eq!(reference_lanes::<64>(keccak_p::<64>([0;25], 24)), f1600);
+        assert_eq!(reference_lanes::<32>(keccak_p::<32>([0;25], 22)), f800);
+        assert_eq!(reference_lanes::<16>(keccak_p::<16>([0;25], 20)), f400);
+        assert_eq!(reference_lanes::<8>(keccak_p::<8>([0;25], 18)), f200);
+        assert_eq!(reference_lanes::<4>(keccak_p::<4>([0;25], 16)), f100);
+        assert_eq!(reference_lanes::<2>(keccak_p::<2>([0;25], 14)), f50);
+        assert_eq!(reference_lanes::<1>(keccak_p::<1>([0;25], 12)), f25);
+
+        // and the second permutation
+        let twice = reference_lanes::<64>(keccak(keccak([0;25], 24), 24));
+        assert_eq!(twice[..5], [0x2d5c954df96ecb3c, 0x6a332cd07057b56d, 0x093d8d1270d76b6c, 0x8a20d9b25569d094, 0x4f9c4f99e5e7f156]);
+    }
+
+    #[test]
+    fn lane_level_equivalence() {
+        // a simple xorshift generator to fill the states
+        let mut seed = 0x9e3779b97f4a7c15u64;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        for rounds in [0, 1, 5, 12, 24] {
+            for _ in 0..4 {
+                let mut state = [0u64;25];
+                for lane in state.iter_mut() {
+                    *lane = next();
+                }
+
+                let mut reference = State::<64>::from_words(&state);
+                for i in (24 - rounds)..24 {
+                    reference = iota(chi(pi(rho(theta(reference)))), i);
+                }
+
+                keccak_f1600(&mut state, rounds);
+                assert_eq!(state, reference.to_words());
+            }
+        }
+    }
+
+    #[test]
+    fn interleaved_states() {
+        let mut states = [[0u64;8];25];
+        for (i, lanes) in states.iter_mut().enumerate() {
+            for (k, lane) in lanes.iter_mut().enumerate() {
+                *lane = (i as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15) ^ (k as u64) << 40;
+            }
+        }
+        let mut expected = [[0u64;25];8];
+        for k in 0..8 {
+            expected[k] = states.map(|lanes| lanes[k]);
+            keccak_f1600(&mut expected[k], 24);
+        }
+
+        keccak_f1600_many(&mut states, 24);
+        for k in 0..8 {
+            assert_eq!(states.map(|lanes| lanes[k]), expected[k]);
+        }
+    }
+
+    #[test]
+    fn const_permutation() {
+        const ZERO:[u64;25] = keccak_f1600_const([0;25], 24);
+        assert_eq!(ZERO, keccak([0;25], 24));
+
+        let mut state = [0u64;25];
+        for (i, lane) in state.iter_mut().enumerate() {
+            *lane = (i as u64).wrapping_mul(0x0123456789abcdef);
+        }
+        for rounds in [0, 1, 12, 24] {
+            assert_eq!(keccak_f1600_const(state, rounds), keccak(state, rounds));
+        }
+    }
+
+    #[test]
+    fn precomputed_constants() {
+        for i in 0..24 {
+            let state = iota(State::<64>::default(), i);
+            assert_eq!(state[0][0].0, ROUND_CONSTANTS[i as usize]);
+        }
+
+        // rotating a single bit by rho shows the offset of every lane
+        for x in 0..5 {
+            for y in 0..5 {
+                let mut state = State::<64>::default();
+                state[x][y].set(0, 1);
+                let offset = ROTATION_OFFSETS[(x + 5*y) as usize] as i64;
+                assert_eq!(rho(state)[x][y].get(offset), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn round_counts() {
+        assert_eq!(total_rounds::<1>(), 12);
+        assert_eq!(total_rounds::<8>(), 18);
+        assert_eq!(total_rounds::<64>(), 24);
+    }
 }
\ No newline at end of file
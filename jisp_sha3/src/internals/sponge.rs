@@ -1,11 +1,28 @@
 //! The internals of SHA-3 with freely adjustable parameters. Only use if you know what you are doing
 
-use crate::internals::keccak::keccak;
-use crate::preprocessing::{padding, split_bytes};
+use crate::internals::keccak::keccak_f1600;
+use crate::preprocessing::{bytes_to_lane, lane_to_bytes};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use crate::internals::keccak::{keccak, keccak_p, total_rounds};
+#[cfg(feature = "alloc")]
+use crate::preprocessing::{padding, padding_bits, split_bytes, BitString};
 
 
 /// Performs the keccak\[c\] algorithm and provides an output of `output` bits long
-pub fn keccak_c<const RATE:usize>(m:&Vec<u8>, suffix:&Vec<bool>, output:usize) -> Vec<u8> {
+#[cfg(feature = "alloc")]
+pub fn keccak_c<const RATE:usize>(m:&[u8], suffix:&[bool], output:usize) -> Vec<u8> {
+    let mut sponge = Sponge::<RATE>::new(suffix);
+    sponge.absorb(m);
+    sponge.squeeze(output)
+}
+
+/// The original version of [keccak_c], which reads every byte starting at its most significant bit 
+/// and also outputs its bytes in that order. Used by the [legacy](crate::sha3::legacy) functions
+#[cfg(feature = "alloc")]
+#[allow(clippy::needless_range_loop, clippy::needless_return)]
+pub fn legacy_keccak_c<const RATE:usize>(m:&Vec<u8>, suffix:&Vec<bool>, output:usize) -> Vec<u8> {
     let blocks = padding::<RATE>(m, suffix);
     let rounds = 24; //rounds per block
     //absorb blocks
@@ -43,4 +60,288 @@ pub fn keccak_c<const RATE:usize>(m:&Vec<u8>, suffix:&Vec<bool>, output:usize) -
     }
 
     return result;
-}
\ No newline at end of file
+}
+
+/// Performs the keccak\[c\] algorithm on a message of any number of bits and provides an output of exactly `output` bits long
+#[cfg(feature = "alloc")]
+pub fn keccak_c_bits<const RATE:usize>(m:&BitString, suffix:&Vec<bool>, output:usize) -> BitString {
+    let blocks = padding_bits::<RATE>(m, suffix);
+    let rounds = 24; //rounds per block
+    //absorb blocks
+    let mut state = [0u64; 25];
+
+    for block in blocks {
+        for i in 0..RATE {
+            state[i] ^= block[i];
+        }
+        state = keccak(state, rounds);
+    }
+
+    //squeeze bits
+    let mut result = BitString::new();
+    loop {
+        for i in 0..RATE*64 {
+            if result.len() >= output {
+                return result;
+            }
+            result.push((state[i / 64] >> (63 - i % 64)) & 1 == 1);
+        }
+        state = keccak(state, rounds);
+    }
+}
+
+/// The sponge construction on top of Keccak-f\[25W\] with a rate of `rate` bits, for building smaller toy versions of SHA-3. 
+/// The message is followed by the suffix and pad10*1 before it is absorbed, after which exactly `output` bits are squeezed out. 
+/// Bit `i` of a block ends up in lane `i / W` of the state, at position `i % W` of the lane
+///
+/// # Panics
+/// If `W` is not a valid lane width, or if the rate is 0 or not smaller than the `25W` bits of the state
+///
+/// # Example
+/// ```
+/// use jisp_sha3::internals::sponge::keccak_w;
+/// use jisp_sha3::preprocessing::BitString;
+///
+/// // a toy hash function on Keccak-f[200] with a capacity of 160 bits
+/// let m = BitString::from_bytes(b"abc", 24);
+/// let hash = keccak_w::<8>(&m, 40, &vec![false, true], 160);
+///
+/// assert_eq!(hash.as_bytes()[..4], [0x91, 0x8f, 0x28, 0x09]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn keccak_w<const W:usize>(m:&BitString, rate:usize, suffix:&Vec<bool>, output:usize) -> BitString {
+    if rate == 0 || rate >= 25*W {panic!("A rate of {} bits does not fit in a state of {} bits!", rate, 25*W)}
+    let rounds = total_rounds::<W>();
+
+    let mut bits = m.clone();
+    for b in suffix {
+        bits.push(*b);
+    }
+    //pad10*1
+    bits.push(true);
+    while !(bits.len() + 1).is_multiple_of(rate) {
+        bits.push(false);
+    }
+    bits.push(true);
+
+    //absorb blocks
+    let mut state = [0u64; 25];
+    for start in (0..bits.len()).step_by(rate) {
+        for i in 0..rate {
+            if bits.get(start + i) {
+                state[i / W] ^= 1 << (W - 1 - i % W);
+            }
+        }
+        state = keccak_p::<W>(state, rounds);
+    }
+
+    //squeeze bits
+    let mut result = BitString::new();
+    loop {
+        for i in 0..rate {
+            if result.len() >= output {
+                return result;
+            }
+            result.push((state[i / W] >> (W - 1 - i % W)) & 1 == 1);
+        }
+        state = keccak_p::<W>(state, rounds);
+    }
+}
+
+/// The keccak\[c\] sponge construction in a form that can absorb a message in multiple parts.
+/// Absorbing a message in pieces gives the same result as passing it to [keccak_c] in one go. 
+/// It works on a fixed buffer and never allocates
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use jisp_sha3::internals::sponge::{Sponge, keccak_c};
+///
+/// let suffix = vec![false, true];
+/// let mut sponge = Sponge::<17>::new(&suffix);
+/// sponge.absorb(&[1, 2, 3]);
+/// sponge.absorb(&[4, 5]);
+///
+/// let expected = keccak_c::<17>(&vec![1, 2, 3, 4, 5], &suffix, 256);
+/// assert_eq!(sponge.squeeze(256), expected);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sponge<const RATE:usize> {
+    state:[u64;25],
+    block:[u8;200],
+    pos:usize,
+    /// The bits of the suffix, starting at the least significant bit
+    suffix:u64,
+    suffix_len:usize,
+    rounds:i64,
+}
+
+impl<const RATE:usize> Sponge<RATE> {
+    /// # Panics
+    /// If the suffix is longer than 64 bits
+    pub fn new(suffix:&[bool]) -> Self {
+        Self::with_rounds(suffix, 24)
+    }
+
+    /// A sponge that uses the given number of rounds of the keccak permutation instead of the standard 24, 
+    /// the last rounds are used when this is less than 24
+    ///
+    /// # Panics
+    /// If the suffix is longer than 64 bits
+    pub fn with_rounds(suffix:&[bool], rounds:i64) -> Self {
+        if suffix.len() > 64 {panic!("A suffix of {} bits is too long, it can be at most 64 bits!", suffix.len())}
+        let mut bits = 0;
+        for (i, bit) in suffix.iter().enumerate() {
+            bits |= (*bit as u64) << i;
+        }
+        Sponge { state: [0;25], block: [0;200], pos: 0, suffix: bits, suffix_len: suffix.len(), rounds }
+    }
+
+    /// Absorbs the next part of the message, full blocks are permuted into the state right away
+    pub fn absorb(&mut self, m:&[u8]) {
+        for byte in m {
+            self.block[self.pos] = *byte;
+            self.pos += 1;
+
+            if self.pos >= RATE*8 {
+                self.absorb_block();
+            }
+        }
+    }
+
+    /// Pads the remainder of the message and squeezes out `output` bits
+    #[cfg(feature = "alloc")]
+    pub fn squeeze(self, output:usize) -> Vec<u8> {
+        let mut result = vec![0u8; output.div_ceil(8)];
+        self.finalize().read(&mut result);
+        result
+    }
+
+    /// Pads the remainder of the message and returns a reader that squeezes out as many bytes as requested
+    pub fn finalize(mut self) -> SpongeReader<RATE> {
+        //the suffix and the first bit of pad10*1, which can run into the next block
+        let mut bit = self.pos*8;
+        for i in 0..=self.suffix_len {
+            if bit == RATE*64 {
+                self.absorb_block();
+                bit = 0;
+            }
+            if i == self.suffix_len || (self.suffix >> i) & 1 == 1 {
+                self.block[bit / 8] |= 1 << (bit % 8);
+            }
+            bit += 1;
+        }
+        if bit == RATE*64 {
+            self.absorb_block();
+        }
+
+        //the last bit of pad10*1
+        self.block[RATE*8 - 1] |= 0x80;
+        self.absorb_block();
+
+        SpongeReader { state: self.state, pos: 0, rounds: self.rounds }
+    }
+
+    /// Permutes the block in the buffer into the state and clears the buffer
+    fn absorb_block(&mut self) {
+        for i in 0..RATE {
+            self.state[i] ^= bytes_to_lane(self.block[8*i..8*i + 8].try_into().unwrap());
+        }
+        keccak_f1600(&mut self.state, self.rounds);
+        self.block = [0;200];
+        self.pos = 0;
+    }
+}
+
+/// The squeezing phase of a [Sponge], bytes can be read in any number of calls 
+/// and together they form the same output as a single large [squeeze](Sponge::squeeze)
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use jisp_sha3::internals::sponge::Sponge;
+///
+/// let suffix = vec![true;4];
+/// let mut sponge = Sponge::<21>::new(&suffix);
+/// sponge.absorb(&[1, 2, 3]);
+/// let expected = sponge.clone().squeeze(400*8);
+///
+/// let mut reader = sponge.finalize();
+/// let mut result = vec![0u8; 400];
+/// reader.read(&mut result[..150]);
+/// reader.read(&mut result[150..]);
+///
+/// assert_eq!(result, expected);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpongeReader<const RATE:usize> {
+    state:[u64;25],
+    pos:usize,
+    rounds:i64,
+}
+
+impl<const RATE:usize> SpongeReader<RATE> {
+    /// Fills `buffer` with the next bytes of output, the state is permuted whenever a block runs out
+    pub fn read(&mut self, buffer:&mut [u8]) {
+        for byte in buffer.iter_mut() {
+            if self.pos >= RATE*8 {
+                keccak_f1600(&mut self.state, self.rounds);
+                self.pos = 0;
+            }
+            *byte = lane_to_bytes(self.state[self.pos / 8])[self.pos % 8];
+            self.pos += 1;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{from_str, to_hex};
+
+    #[test]
+    fn toy_sponges() {
+        let abc = BitString::from_bytes(b"abc", 24);
+        assert_eq!(to_hex(keccak_w::<8>(&abc, 40, &vec![false, true], 160).as_bytes()), "918f28095ae37717dd78896bea9ad184cdb31491");
+        assert_eq!(to_hex(keccak_w::<16>(&abc, 144, &vec![true;4], 300).as_bytes()), 
+            "86d474eb978d7484716a008aa309888d0639222ea97b90ffe0c262bee8f75389a17cd5fe3706");
+        assert_eq!(to_hex(keccak_w::<32>(&abc, 288, &vec![false, true], 256).as_bytes()), 
+            "adb82aea84d2b0545f178b1c6324ddbeb4feed5676fa77f6925df88e323c188b");
+
+        // several blocks and a message that does not fill a whole byte
+        let mut m = BitString::from_bytes(&(0..50).collect::<Vec<u8>>(), 400);
+        for bit in [true, false, true] {
+            m.push(bit);
+        }
+        assert_eq!(to_hex(keccak_w::<8>(&m, 40, &vec![], 160).as_bytes()), "49fd2f36353a3fe2c2ce649801fb170aeee7ce73");
+
+        // a rate that is not a multiple of the lane width
+        let hash = keccak_w::<1>(&from_str("10110"), 9, &vec![], 20);
+        assert_eq!(hash, from_str("10100111110110001010"));
+    }
+
+    #[test]
+    fn padding_across_blocks() {
+        let suffix = vec![true, false, true, true, false, true, true];
+        for len in [134, 135, 136] {
+            let m = vec![0x5a; len];
+            let expected = keccak_c_bits::<17>(&BitString::from_bytes(&m, len*8), &suffix, 512);
+            assert_eq!(keccak_c::<17>(&m, &suffix, 512), expected.as_bytes());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn suffix_too_long() {
+        Sponge::<17>::new(&[true; 65]);
+    }
+
+    #[test]
+    fn full_width_is_sha3() {
+        let m = BitString::from_bytes(&[0xa3; 201], 1605);
+        let suffix = vec![false, true];
+        assert_eq!(keccak_w::<64>(&m, 1088, &suffix, 256), keccak_c_bits::<17>(&m, &suffix, 256));
+    }
+}
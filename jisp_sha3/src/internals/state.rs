@@ -1,5 +1,7 @@
 //! The internal state in the keccak algorithm
-use std::ops::{Index, IndexMut, BitXor};
+use core::ops::{Index, IndexMut, BitXor};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 
 /// Turns a string of `u64` words into a state matrix
 /// 
@@ -18,64 +20,95 @@ use std::ops::{Index, IndexMut, BitXor};
 /// assert_eq!(state[4][0].0, 1);
 /// assert_eq!(state[0][1].0, 2);
 /// ```
+#[cfg(feature = "alloc")]
+#[allow(clippy::ptr_arg)]
 pub fn to_state(v:&Vec<u64>) -> State {
-    let mut res = State::default();
-    let mut x = 0;
-    let mut y = 0;
-
-    for word in v {
-        res[x][y] = Lane(*word);
-        x = x + 1;
-        if x >= 5 {
-            y = y + 1;
-            x = 0;
-        }
-    }
-
-    return res;
+    State::from_words(v)
 }
 
 /// Transforms a State back into a string of `u64` words
 /// 
 /// # Examples
 /// ```
-/// use jisp_sha3::internals::state::{to_state, from_state};
+/// use jisp_sha3::internals::state::{State, from_state};
 /// 
 /// let mut v = [0;25];
 /// v[4] = 1;
 /// v[5] = 2;
 /// 
-/// let state = to_state(&v.to_vec());
+/// let state = State::from_words(&v);
 /// let v2 = from_state(&state);
 /// 
 /// assert_eq!(v,v2);
 /// ```
 pub fn from_state(state:&State) -> [u64;25] {
-    let mut res = [0;25];
-
-    for x in 0..5 {
-        for y in 0..5 {
-            let i = (x + y*5) as usize;
-            res[i] = state[x][y].0;
-        }
-    }
-    return res;
+    state.to_words()
 }
 
+/// A lane of `W` bits, stored in the least significant bits of a `u64`. 
+/// The width `W` is one of 1, 2, 4, 8, 16, 32 or 64, where 64 gives the lanes of Keccak-f\[1600\] used by SHA-3
 #[derive(Debug, Default, Clone, Copy)]
-pub struct Lane(pub u64);
+pub struct Lane<const W:usize = 64>(pub u64);
 
 #[derive(Debug, Default, Clone, Copy)]
-pub struct Sheet(pub [Lane;5]);
+pub struct Sheet<const W:usize = 64>(pub [Lane<W>;5]);
 
+/// The state of the Keccak-f\[25W\] permutation, made up of 5 by 5 lanes of `W` bits
 #[derive(Debug, Default, Clone, Copy)]
-pub struct State(pub [Sheet;5]);
+pub struct State<const W:usize = 64>(pub [Sheet<W>;5]);
+
+impl<const W:usize> State<W> {
+    /// Turns a string of words into a state matrix, each word holding one lane in its `W` least significant bits
+    ///
+    /// # Panics
+    /// If the string of words has more than 25 elements
+    ///
+    /// # Examples
+    /// ```
+    /// use jisp_sha3::internals::state::State;
+    ///
+    /// let state = State::<8>::from_words(&[0, 0, 0, 0, 0xab, 0xcd]);
+    ///
+    /// assert_eq!(state[4][0].0, 0xab);
+    /// assert_eq!(state[0][1].0, 0xcd);
+    /// ```
+    pub fn from_words(v:&[u64]) -> Self {
+        let mut res = Self::default();
+        let mut x = 0;
+        let mut y = 0;
+
+        for word in v {
+            res[x][y] = Lane(*word & Lane::<W>::MASK);
+            x += 1;
+            if x >= 5 {
+                y += 1;
+                x = 0;
+            }
+        }
+
+        res
+    }
+
+    /// Transforms the state back into a string of 25 words
+    pub fn to_words(&self) -> [u64;25] {
+        let mut res = [0;25];
+
+        for x in 0..5 {
+            for y in 0..5 {
+                let i = (x + y*5) as usize;
+                res[i] = self[x][y].0;
+            }
+        }
+        res
+    }
+}
 
 pub trait Modulus {
     fn md(&self, m:usize) -> Self;
 }
 
 impl Modulus for i64 {
+    #[allow(clippy::needless_return)]
     fn md(&self, m:usize) -> i64 {
         let m = m as i64;
         let mut x = *self;
@@ -89,11 +122,15 @@ impl Modulus for i64 {
     }
 }
 
-impl Lane {
+impl<const W:usize> Lane<W> {
+    /// The bits of a `u64` that are part of the lane
+    pub const MASK:u64 = u64::MAX >> (64 - W);
 
+    /// Gets bit `index` of the lane, where bit 0 is the most significant of the `W` bits
+    #[allow(clippy::needless_return)]
     pub fn get(&self, index:i64) -> u8 {
         let num = self.0;
-        let res = (num >> (63 - index.md(64))) % 2;
+        let res = (num >> (W as i64 - 1 - index.md(W))) % 2;
         return res as u8;
     }
 
@@ -101,14 +138,14 @@ impl Lane {
         if self.get(index) != value {
 
             let num = &mut self.0;
-            *num = num.bitxor(1 << (63 - index.md(64)));
+            *num = num.bitxor(1 << (W as i64 - 1 - index.md(W)));
         }
     }
 }
 
 
-impl Index<i64> for Sheet {
-    type Output = Lane;
+impl<const W:usize> Index<i64> for Sheet<W> {
+    type Output = Lane<W>;
 
     fn index(&self, index: i64) -> &Self::Output {
         let Sheet(data) = self;
@@ -116,15 +153,15 @@ impl Index<i64> for Sheet {
     }
 }
 
-impl IndexMut<i64> for Sheet {
+impl<const W:usize> IndexMut<i64> for Sheet<W> {
     fn index_mut(&mut self, index: i64) -> &mut Self::Output {
         let Sheet(data) = self;
         &mut data[index.md(5) as usize]
     }
 }
 
-impl Index<i64> for State {
-    type Output = Sheet;
+impl<const W:usize> Index<i64> for State<W> {
+    type Output = Sheet<W>;
 
     fn index(&self, index: i64) -> &Self::Output {
         let State(data) = self;
@@ -132,7 +169,7 @@ impl Index<i64> for State {
     }
 }
 
-impl IndexMut<i64> for State {
+impl<const W:usize> IndexMut<i64> for State<W> {
     fn index_mut(&mut self, index: i64) -> &mut Self::Output {
         let State(data) = self;
         &mut data[index.md(5) as usize]
@@ -146,18 +183,32 @@ mod tests {
 
     #[test]
     fn lane_get() {
-        let lane = Lane(0b0010);
+        let lane: Lane = Lane(0b0010);
         assert_eq!(lane.get(63), 0);
         assert_eq!(lane.get(62), 1);
     }
 
     #[test]
     fn lane_set() {
-        let mut lane = Lane(1 << 63);
+        let mut lane: Lane = Lane(1 << 63);
         lane.set(0, 1);
         assert_eq!(lane.0, 1 << 63);
         lane.set(1, 1);
         lane.set(0, 0);
         assert_eq!(lane.0, 1 << 62);
     }
+
+    #[test]
+    fn small_lanes() {
+        let mut lane = Lane::<8>(0b1000_0001);
+        assert_eq!(lane.get(0), 1);
+        assert_eq!(lane.get(7), 1);
+        assert_eq!(lane.get(8), 1);
+        lane.set(1, 1);
+        assert_eq!(lane.0, 0b1100_0001);
+
+        let mut lane = Lane::<1>(0);
+        lane.set(5, 1);
+        assert_eq!(lane.0, 1);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,25 @@
+//! Comparison of secret values such as authentication tags
+
+/// Compares two byte strings in constant time, so the time it takes does not reveal how many of the first bytes were correct. 
+/// Only the length of the strings is allowed to leak
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::internals::verify::constant_time_eq;
+///
+/// assert!(constant_time_eq(&[1, 2, 3], &[1, 2, 3]));
+/// assert!(!constant_time_eq(&[1, 2, 3], &[1, 2, 4]));
+/// assert!(!constant_time_eq(&[1, 2, 3], &[1, 2]));
+/// ```
+pub fn constant_time_eq(a:&[u8], b:&[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut diff = 0u8;
+    for i in 0..a.len() {
+        diff |= a[i] ^ b[i];
+    }
+    // keeps the compiler from turning the loop into an early return
+    core::hint::black_box(diff) == 0
+}
@@ -0,0 +1,200 @@
+//! KangarooTwelve (KT128 and KT256) as defined in RFC 9861, a fast hash function built on [TurboSHAKE](crate::turboshake). 
+//!
+//! Next to the message it takes a customization string `c`. Messages of up to 8 KiB are hashed directly, 
+//! longer messages are split into chunks of 8 KiB that are hashed into chaining values, which are then combined using the Sakura encoding. 
+//! The output can have any length, `l` bits are returned or a [SpongeReader] can be used to read as much as needed
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::kangarootwelve::kt128;
+//! use jisp_sha3::printer::print_bytes_be;
+//!
+//! let hash = kt128(b"", b"", 256);
+//!
+//! let expected = "1ac2d450 fc3b4205 d19da7bf ca1b3751 3c080357 7ac7167f 06fe2ce1 f0ef39e5";
+//! assert_eq!(print_bytes_be(&hash), expected);
+//! ```
+use crate::internals::sponge::SpongeReader;
+use crate::preprocessing::length_encode;
+use crate::turboshake::TurboShake;
+use alloc::{vec, vec::Vec};
+
+/// The size of a chunk in bytes
+const CHUNK:usize = 8192;
+
+/// The domain separation bytes of a message that fits in a single node, a chaining value and the final node of a tree
+const SINGLE_NODE:u8 = 0x07;
+const CHAINING_VALUE:u8 = 0x0B;
+const FINAL_NODE:u8 = 0x06;
+
+pub fn kt128(m:&[u8], c:&[u8], l:usize) -> Vec<u8> {
+    let mut hasher = Kt128::new(c);
+    hasher.update(m);
+    hasher.finalize(l)
+}
+
+pub fn kt256(m:&[u8], c:&[u8], l:usize) -> Vec<u8> {
+    let mut hasher = Kt256::new(c);
+    hasher.update(m);
+    hasher.finalize(l)
+}
+
+/// Incremental version of KangarooTwelve, use one of the aliases [Kt128] or [Kt256]
+///
+/// # Example
+/// ```
+/// use jisp_sha3::kangarootwelve::{Kt256, kt256};
+///
+/// let m = vec![0x5a; 20_000];
+/// let mut hasher = Kt256::new(b"customization");
+/// for part in m.chunks(3000) {
+///     hasher.update(part);
+/// }
+///
+/// let mut reader = hasher.finalize_xof();
+/// let mut hash = [0u8; 64];
+/// reader.read(&mut hash);
+///
+/// assert_eq!(hash.to_vec(), kt256(&m, b"customization", 512));
+/// ```
+#[derive(Debug, Clone)]
+pub struct KangarooTwelve<const RATE:usize> {
+    c:Vec<u8>,
+    /// The first chunk, kept until it is clear whether the message fits in a single node
+    first:Vec<u8>,
+    final_node:Option<TurboShake<RATE>>,
+    leaf:Option<TurboShake<RATE>>,
+    leaf_len:usize,
+    /// The number of chunks after the first one
+    leaves:usize,
+}
+
+pub type Kt128 = KangarooTwelve<21>;
+pub type Kt256 = KangarooTwelve<17>;
+
+impl<const RATE:usize> KangarooTwelve<RATE> {
+    pub fn new(c:&[u8]) -> Self {
+        KangarooTwelve { c: c.to_vec(), first: Vec::new(), final_node: None, leaf: None, leaf_len: 0, leaves: 0 }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        let mut m = m;
+        while !m.is_empty() {
+            if self.first.len() < CHUNK {
+                let take = m.len().min(CHUNK - self.first.len());
+                self.first.extend_from_slice(&m[..take]);
+                m = &m[take..];
+                continue;
+            }
+
+            if self.final_node.is_none() {
+                let mut node = TurboShake::new(FINAL_NODE);
+                node.update(&self.first);
+                node.update(&[0x03, 0, 0, 0, 0, 0, 0, 0]);
+                self.final_node = Some(node);
+            }
+
+            if self.leaf_len == CHUNK {
+                self.finish_leaf();
+            }
+            let leaf = self.leaf.get_or_insert_with(|| TurboShake::new(CHAINING_VALUE));
+            let take = m.len().min(CHUNK - self.leaf_len);
+            leaf.update(&m[..take]);
+            self.leaf_len += take;
+            m = &m[take..];
+        }
+    }
+
+    /// Finishes the message and returns `l` bits of output
+    pub fn finalize(self, l:usize) -> Vec<u8> {
+        let mut res = vec![0u8; l.div_ceil(8)];
+        self.finalize_xof().read(&mut res);
+        res
+    }
+
+    /// Finishes the message and returns a reader for an output of unknown length
+    pub fn finalize_xof(mut self) -> SpongeReader<RATE> {
+        let c = core::mem::take(&mut self.c);
+        self.update(&c);
+        self.update(&length_encode(c.len()));
+
+        if self.final_node.is_none() {
+            let mut node = TurboShake::<RATE>::new(SINGLE_NODE);
+            node.update(&self.first);
+            return node.finalize_xof();
+        }
+
+        self.finish_leaf();
+        let mut node = self.final_node.unwrap();
+        node.update(&length_encode(self.leaves));
+        node.update(&[0xff, 0xff]);
+        node.finalize_xof()
+    }
+
+    /// Adds the chaining value of the current chunk to the final node, its size is the capacity of the sponge
+    fn finish_leaf(&mut self) {
+        if let (Some(leaf), Some(node)) = (self.leaf.take(), self.final_node.as_mut()) {
+            let cv_size = 200 - RATE*8;
+            node.update(&leaf.finalize(cv_size*8));
+            self.leaves += 1;
+            self.leaf_len = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{ptn, to_hex};
+
+    // The test vectors from RFC 9861
+    #[test]
+    fn kt128_rfc_vectors() {
+        assert_eq!(to_hex(&kt128(b"", b"", 256)), "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5");
+        assert_eq!(to_hex(&kt128(b"", b"", 10032*8)[10000..]), "e8dc563642f7228c84684c898405d3a834799158c079b12880277a1d28e2ff6d");
+
+        assert_eq!(to_hex(&kt128(&ptn(1), b"", 256)), "2bda92450e8b147f8a7cb629e784a058efca7cf7d8218e02d345dfaa65244a1f");
+        assert_eq!(to_hex(&kt128(&ptn(17), b"", 256)), "6bf75fa2239198db4772e36478f8e19b0f371205f6a9a93a273f51df37122888");
+        assert_eq!(to_hex(&kt128(&ptn(17*17), b"", 256)), "0c315ebcdedbf61426de7dcf8fb725d1e74675d7f5327a5067f367b108ecb67c");
+        assert_eq!(to_hex(&kt128(&ptn(17*17*17), b"", 256)), "cb552e2ec77d9910701d578b457ddf772c12e322e4ee7fe417f92c758f0d59d0");
+        assert_eq!(to_hex(&kt128(&ptn(17*17*17*17), b"", 256)), "8701045e22205345ff4dda05555cbb5c3af1a771c2b89baef37db43d9998b9fe");
+
+        assert_eq!(to_hex(&kt128(b"", &ptn(1), 256)), "fab658db63e94a246188bf7af69a133045f46ee984c56e3c3328caaf1aa1a583");
+        assert_eq!(to_hex(&kt128(&[0xff], &ptn(41), 256)), "d848c5068ced736f4462159b9867fd4c20b808acc3d5bc48e0b06ba0a3762ec4");
+        assert_eq!(to_hex(&kt128(&[0xff; 3], &ptn(41*41), 256)), "c389e5009ae57120854c2e8c64670ac01358cf4c1baf89447a724234dc7ced74");
+        assert_eq!(to_hex(&kt128(&[0xff; 7], &ptn(41*41*41), 256)), "75d2f86a2e644566726b4fbcfc5657b9dbcf070c7b0dca06450ab291d7443bcf");
+
+        assert_eq!(to_hex(&kt128(&ptn(8191), b"", 256)), "1b577636f723643e990cc7d6a659837436fd6a103626600eb8301cd1dbe553d6");
+        assert_eq!(to_hex(&kt128(&ptn(8192), b"", 256)), "48f256f6772f9edfb6a8b661ec92dc93b95ebd05a08a17b39ae3490870c926c3");
+        assert_eq!(to_hex(&kt128(&ptn(8192), &ptn(8189), 256)), "3ed12f70fb05ddb58689510ab3e4d23c6c6033849aa01e1d8c220a297fedcd0b");
+        assert_eq!(to_hex(&kt128(&ptn(8192), &ptn(8190), 256)), "6a7c1b6a5cd0d8c9ca943a4a216cc64604559a2ea45f78570a15253d67ba00ae");
+    }
+
+    #[test]
+    fn kt256_rfc_vectors() {
+        assert_eq!(to_hex(&kt256(b"", b"", 512)), 
+            "b23d2e9cea9f4904e02bec06817fc10ce38ce8e93ef4c89e6537076af8646404e3e8b68107b8833a5d30490aa33482353fd4adc7148ecb782855003aaebde4a9");
+        assert_eq!(to_hex(&kt256(&ptn(17), b"", 512)), 
+            "1ba3c02b1fc514474f06c8979978a9056c8483f4a1b63d0dccefe3a28a2f323e1cdcca40ebf006ac76ef0397152346837b1277d3e7faa9c9653b19075098527b");
+        assert_eq!(to_hex(&kt256(&ptn(17*17), b"", 512)), 
+            "de8ccbc63e0f133ebb4416814d4c66f691bbf8b6a61ec0a7700f836b086cb029d54f12ac7159472c72db118c35b4e6aa213c6562caaa9dcc518959e69b10f3ba");
+        assert_eq!(to_hex(&kt256(&ptn(17*17*17*17), b"", 512)), 
+            "b06275d284cd1cf205bcbe57dccd3ec1ff6686e3ed15776383e1f2fa3c6ac8f08bf8a162829db1a44b2a43ff83dd89c3cf1ceb61ede659766d5ccf817a62ba8d");
+        assert_eq!(to_hex(&kt256(&ptn(8192), &ptn(8190), 512)), 
+            "f4b5908b929ffe01e0f79ec2f21243d41a396b2e7303a6af1d6399cd6c7a0a2dd7c4f607e8277f9c9b1cb4ab9ddc59d4b92d1fc7558441f1832c3279a4241b8b");
+    }
+
+    #[test]
+    fn incremental_across_chunks() {
+        let m = ptn(3*CHUNK + 100);
+        let expected = kt128(&m, b"c", 256);
+        for part_size in [1000, CHUNK, CHUNK + 1] {
+            let mut hasher = Kt128::new(b"c");
+            for part in m.chunks(part_size) {
+                hasher.update(part);
+            }
+            assert_eq!(hasher.finalize(256), expected);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! The original Keccak hash functions from the SHA-3 competition, before the standardization in FIPS 202. 
+//!
+//! These only differ from [SHA-3](crate::sha3) in the padding: the message is followed directly by pad10*1, 
+//! without the `01` suffix that FIPS 202 added for domain separation. This is the version used by Ethereum, 
+//! where `keccak256` is often incorrectly called SHA3-256
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use jisp_sha3::keccak::keccak256;
+//! use jisp_sha3::printer::print_bytes_be;
+//!
+//! let hash = keccak256(b"");
+//!
+//! let expected = "c5d24601 86f7233c 927e7db2 dcc703c0 e500b653 ca82273b 7bfad804 5d85a470";
+//! assert_eq!(print_bytes_be(&hash), expected);
+//! # }
+//! ```
+use crate::internals::sponge::Sponge;
+#[cfg(feature = "alloc")]
+use crate::internals::sponge::keccak_c;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+pub fn keccak224(m:&[u8]) -> Vec<u8> {
+    keccak_c::<18>(m, &[], 224)
+}
+#[cfg(feature = "alloc")]
+pub fn keccak256(m:&[u8]) -> Vec<u8> {
+    keccak_c::<17>(m, &[], 256)
+}
+#[cfg(feature = "alloc")]
+pub fn keccak384(m:&[u8]) -> Vec<u8> {
+    keccak_c::<13>(m, &[], 384)
+}
+#[cfg(feature = "alloc")]
+pub fn keccak512(m:&[u8]) -> Vec<u8> {
+    keccak_c::<9>(m, &[], 512)
+}
+
+/// Incremental version of the Keccak functions, use one of the aliases [Keccak224], [Keccak256], [Keccak384] or [Keccak512]
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use jisp_sha3::keccak::{Keccak256, keccak256};
+///
+/// let mut hasher = Keccak256::new();
+/// hasher.update(b"transfer(address,");
+/// hasher.update(b"uint256)");
+///
+/// let hash = hasher.finalize();
+/// assert_eq!(hash, keccak256(b"transfer(address,uint256)"));
+/// assert_eq!(hash[..4], [0xa9, 0x05, 0x9c, 0xbb]);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keccak<const RATE:usize, const DIGEST:usize> {
+    sponge:Sponge<RATE>,
+}
+
+pub type Keccak224 = Keccak<18, 224>;
+pub type Keccak256 = Keccak<17, 256>;
+pub type Keccak384 = Keccak<13, 384>;
+pub type Keccak512 = Keccak<9, 512>;
+
+impl<const RATE:usize, const DIGEST:usize> Keccak<RATE, DIGEST> {
+    pub fn new() -> Self {
+        Keccak { sponge: Sponge::new(&[]) }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        self.sponge.absorb(m);
+    }
+
+    /// Finishes the message and returns the `DIGEST` bit hash
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self) -> Vec<u8> {
+        self.sponge.squeeze(DIGEST)
+    }
+
+    /// Finishes the message and writes the `DIGEST` bit hash to `out`, without allocating
+    ///
+    /// # Panics
+    /// If `out` is not `DIGEST / 8` bytes long
+    pub fn finalize_into(self, out:&mut [u8]) {
+        if out.len() != DIGEST / 8 {panic!("A buffer of {} bytes does not fit a hash of {} bits!", out.len(), DIGEST)}
+        self.sponge.finalize().read(out);
+    }
+}
+
+impl<const RATE:usize, const DIGEST:usize> Default for Keccak<RATE, DIGEST> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::test_helpers::to_hex;
+
+    #[test]
+    fn finalize_into_buffer() {
+        let mut hasher = Keccak256::new();
+        hasher.update(b"");
+        let mut hash = [0u8; 32];
+        hasher.finalize_into(&mut hash);
+        assert_eq!(hash[..8], [0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn finalize_into_wrong_length() {
+        Keccak512::new().finalize_into(&mut [0u8; 32]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn known_values() {
+        assert_eq!(to_hex(&keccak224(b"")), "f71837502ba8e10837bdd8d365adb85591895602fc552b48b7390abd");
+        assert_eq!(to_hex(&keccak256(b"")), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(to_hex(&keccak384(b"")), 
+            "2c23146a63a29acf99e73b88f8c24eaa7dc60aa771780ccc006afbfa8fe2479b2dd2b21362337441ac12b515911957ff");
+        assert_eq!(to_hex(&keccak512(b"")), 
+            "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e");
+
+        assert_eq!(to_hex(&keccak256(b"abc")), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+        assert_eq!(to_hex(&keccak256(b"The quick brown fox jumps over the lazy dog")), 
+            "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15");
+        // longer than a block
+        assert_eq!(to_hex(&keccak256(&[b'a'; 200])), "96ea54061def936c4be90b518992fdc6f12f535068a256229aca54267b4d084d");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn incremental_matches_one_shot() {
+        let m:Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let mut hasher = Keccak512::new();
+        for part in m.chunks(71) {
+            hasher.update(part);
+        }
+        assert_eq!(hasher.finalize(), keccak512(&m));
+
+        let mut hasher = Keccak224::new();
+        hasher.update(&m);
+        assert_eq!(hasher.finalize(), keccak224(&m));
+    }
+}
@@ -0,0 +1,195 @@
+//! KMAC128 and KMAC256 as defined in NIST SP 800-185, message authentication codes built on [cSHAKE](crate::cshake). 
+//!
+//! Next to the key and the message they take the requested output length `l` in bits and a customization string `s`. 
+//! The length is part of the input, so tags of different lengths are unrelated to each other. 
+//! The KMACXOF variants have an output of arbitrary length instead, here any prefix of a longer tag is also a valid tag.
+//! Tags shorter than [MIN_TAG_LEN] bytes are never accepted by [Kmac::verify] and [Kmac::verify_xof]
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::kmac::{kmac128, Kmac128};
+//!
+//! let key = b"a secret key of at least 16 bytes";
+//! let tag = kmac128(key, b"message", 256, b"my protocol");
+//!
+//! let mut mac = Kmac128::new(key, b"my protocol");
+//! mac.update(b"mess");
+//! mac.update(b"age");
+//! assert!(mac.verify(&tag));
+//! ```
+use crate::cshake::{cshake128, cshake256, CShake};
+use crate::internals::sponge::SpongeReader;
+use crate::internals::verify::constant_time_eq;
+use crate::preprocessing::{bytepad, encode_string, right_encode};
+use alloc::{vec, vec::Vec};
+
+/// The shortest tag in bytes that is accepted, SP 800-185 does not allow outputs of less than 32 bits for a MAC
+pub const MIN_TAG_LEN:usize = 4;
+
+pub fn kmac128(k:&[u8], x:&[u8], l:usize, s:&[u8]) -> Vec<u8> {
+    let x = new_x::<21>(k, x, l);
+    cshake128(&x, l, b"KMAC", s)
+}
+
+pub fn kmac256(k:&[u8], x:&[u8], l:usize, s:&[u8]) -> Vec<u8> {
+    let x = new_x::<17>(k, x, l);
+    cshake256(&x, l, b"KMAC", s)
+}
+
+/// KMAC128 with an arbitrary output length, `l` bits are returned
+pub fn kmacxof128(k:&[u8], x:&[u8], l:usize, s:&[u8]) -> Vec<u8> {
+    let x = new_x::<21>(k, x, 0);
+    cshake128(&x, l, b"KMAC", s)
+}
+
+/// KMAC256 with an arbitrary output length, `l` bits are returned
+pub fn kmacxof256(k:&[u8], x:&[u8], l:usize, s:&[u8]) -> Vec<u8> {
+    let x = new_x::<17>(k, x, 0);
+    cshake256(&x, l, b"KMAC", s)
+}
+
+/// `bytepad(encode_string(k), rate) || x || right_encode(l)`, where an `l` of 0 is used for the XOF variants
+fn new_x<const RATE:usize>(k:&[u8], x:&[u8], l:usize) -> Vec<u8> {
+    let mut res = bytepad(&encode_string(k), RATE*8);
+    res.extend_from_slice(x);
+    res.extend(right_encode(l));
+    res
+}
+
+/// Incremental version of KMAC, use one of the aliases [Kmac128] or [Kmac256]
+#[derive(Debug, Clone)]
+pub struct Kmac<const RATE:usize> {
+    cshake:CShake<RATE>,
+}
+
+pub type Kmac128 = Kmac<21>;
+pub type Kmac256 = Kmac<17>;
+
+impl<const RATE:usize> Kmac<RATE> {
+    pub fn new(k:&[u8], s:&[u8]) -> Self {
+        let mut cshake = CShake::new(b"KMAC", s);
+        cshake.update(&bytepad(&encode_string(k), RATE*8));
+        Kmac { cshake }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        self.cshake.update(m);
+    }
+
+    /// Finishes the message and returns a tag of `l` bits
+    pub fn finalize(mut self, l:usize) -> Vec<u8> {
+        self.cshake.update(&right_encode(l));
+        self.cshake.finalize(l)
+    }
+
+    /// Finishes the message as KMACXOF and returns a reader for an output of unknown length
+    pub fn finalize_xof(mut self) -> SpongeReader<RATE> {
+        self.cshake.update(&right_encode(0));
+        self.cshake.finalize_xof()
+    }
+
+    /// Checks whether `tag` is the KMAC of the message, using its length as the output length. 
+    /// The tags are compared in constant time, tags shorter than [MIN_TAG_LEN] bytes are rejected
+    pub fn verify(self, tag:&[u8]) -> bool {
+        if tag.len() < MIN_TAG_LEN {
+            return false;
+        }
+        let expected = self.finalize(tag.len()*8);
+        constant_time_eq(&expected, tag)
+    }
+
+    /// Checks whether `tag` is the start of the KMACXOF output of the message, compared in constant time.
+    /// Tags shorter than [MIN_TAG_LEN] bytes are rejected
+    pub fn verify_xof(self, tag:&[u8]) -> bool {
+        if tag.len() < MIN_TAG_LEN {
+            return false;
+        }
+        let mut expected = vec![0u8; tag.len()];
+        self.finalize_xof().read(&mut expected);
+        constant_time_eq(&expected, tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::to_hex;
+
+    // The samples published by NIST for SP 800-185
+    #[test]
+    fn nist_samples() {
+        let k:Vec<u8> = (0x40..0x60).collect();
+        let short:Vec<u8> = (0..4).collect();
+        let long:Vec<u8> = (0..200).collect();
+        let s = b"My Tagged Application";
+
+        assert_eq!(to_hex(&kmac128(&k, &short, 256, b"")), "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e");
+        assert_eq!(to_hex(&kmac128(&k, &short, 256, s)), "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5");
+        assert_eq!(to_hex(&kmac128(&k, &long, 256, s)), "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230");
+        assert_eq!(to_hex(&kmac256(&k, &short, 512, s)), 
+            "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd");
+        assert_eq!(to_hex(&kmac256(&k, &long, 512, b"")), 
+            "75358cf39e41494e949707927cee0af20a3ff553904c86b08f21cc414bcfd691589d27cf5e15369cbbff8b9a4c2eb17800855d0235ff635da82533ec6b759b69");
+
+        assert_eq!(to_hex(&kmacxof128(&k, &short, 256, b"")), "cd83740bbd92ccc8cf032b1481a0f4460e7ca9dd12b08a0c4031178bacd6ec35");
+        assert_eq!(to_hex(&kmacxof256(&k, &long, 512, s)), 
+            "d5be731c954ed7732846bb59dbe3a8e30f83e77a4bff4459f2f1c2b4ecebb8ce67ba01c62e8ab8578d2d499bd1bb276768781190020a306a97de281dcc30305d");
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let k = b"key";
+        let m:Vec<u8> = (0..250).map(|i| i as u8).collect();
+
+        let mut mac = Kmac256::new(k, b"app");
+        let mut xof = Kmac128::new(k, b"app");
+        for part in m.chunks(50) {
+            mac.update(part);
+            xof.update(part);
+        }
+        assert_eq!(mac.finalize(320), kmac256(k, &m, 320, b"app"));
+
+        let mut reader = xof.finalize_xof();
+        let mut res = vec![0u8; 300];
+        reader.read(&mut res[..1]);
+        reader.read(&mut res[1..]);
+        assert_eq!(res, kmacxof128(k, &m, 300*8, b"app"));
+    }
+
+    #[test]
+    fn verification() {
+        let k = b"key";
+        let tag = kmac128(k, b"message", 256, b"");
+
+        let mut mac = Kmac128::new(k, b"");
+        mac.update(b"message");
+        assert!(mac.clone().verify(&tag));
+        // the output length is part of the input, so a shorter tag is not valid
+        assert!(!mac.clone().verify(&tag[..16]));
+
+        let mut wrong = tag.clone();
+        wrong[31] ^= 1;
+        assert!(!mac.verify(&wrong));
+
+        let mut mac = Kmac128::new(k, b"");
+        mac.update(b"message");
+        let xof_tag = kmacxof128(k, b"message", 256, b"");
+        assert!(mac.clone().verify_xof(&xof_tag));
+        assert!(mac.verify_xof(&xof_tag[..16]));
+    }
+
+    #[test]
+    fn short_tags_are_rejected() {
+        let mut mac = Kmac256::new(b"key", b"");
+        mac.update(b"message");
+        let tag = mac.clone().finalize(3*8);
+        let xof_tag = kmacxof256(b"key", b"message", 256, b"");
+
+        assert!(!mac.clone().verify(&[]));
+        assert!(!mac.clone().verify(&tag));
+        assert!(!mac.clone().verify_xof(&[]));
+        assert!(!mac.clone().verify_xof(&xof_tag[..MIN_TAG_LEN - 1]));
+        assert!(mac.verify_xof(&xof_tag[..MIN_TAG_LEN]));
+    }
+}
@@ -1,39 +1,99 @@
 //! # About
 //! This crate contains my pure-rust implementations of SHA-3 and its 6 variants, including the extendable output functions [SHAKE128](sha3::shake128) and [SHAKE256](sha3::shake256)
 //!
+//! The functions derived from SHA-3 in NIST SP 800-185 can be found in their own modules, starting with [cSHAKE](cshake). 
+//! HMAC over the SHA-3 functions is available in [hmac] for protocols that need it instead of KMAC, together with the key derivation functions [HKDF](hkdf) and [PBKDF2](pbkdf2).
+//! Messages are expanded to uniform bytes for hashing to elliptic curves with [expand_message_xof](expand_message) from RFC 9380.
+//! The faster [TurboSHAKE](turboshake) and [KangarooTwelve](kangarootwelve) from RFC 9861 use the keccak permutation with only 12 rounds. 
+//! Authenticated encryption is provided by [SpongeWrap](spongewrap), and reproducible random numbers by the generator in [rng]
+//!
 //! # Security
 //! This implementation is just my personal project and has not been officially verified or audited.
 //! It should therefore not be used in any real-world applications, it is only meant for small personal projects such as mine. 
 //! 
 //! # Usage
-//! To perform one of the hashing algorithm variations on your data you first need to parse it into a vector of `u8` bytes. 
-//! You can then simply call one of the functions in [sha3] on your data.
-//! Note that the standard for SHA-3 is to use little endian encoding. You can swap your data to a different encoding scheme using the functions in [preprocessing]
+//! To perform one of the hashing algorithm variations on your data you first need to parse it into `u8` bytes. 
+//! You can then simply call one of the functions in [sha3] on your data, which returns the digest as bytes in the standard FIPS 202 ordering.
+//! Messages that are not a whole number of bytes long can be hashed as a [BitString](preprocessing::BitString) with the `_bits` functions.
+//! Large numbers of short messages can be hashed together with the functions in [batch], 
+//! and digests of constant strings can be computed at compile time with the `const fn` versions in [const_sha3].
+//! 
+//! The crate can be checked against the response files of the NIST CAVP tests with the runner in [cavp].
+//! 
+//! Earlier versions of this crate read every byte starting at its most significant bit, these functions are still available in [sha3::legacy] 
+//! together with the encodings in [preprocessing] and [printer] they need.
+//! 
+//! # Features
+//! - `std` (default): spreads the work of [ParallelHash](parallelhash) and [PBKDF2](pbkdf2) over multiple threads, implies `alloc`
+//! - `alloc`: all functions that return a `Vec` or `String`, which is most of the crate. 
+//!   Without it the crate is an allocation-free `no_std` core containing the [keccak permutation](internals::keccak), 
+//!   the [sponge](internals::sponge::Sponge) and the incremental hashers, which write their output to a caller's buffer
+//! - `digest`: implements the [RustCrypto](https://github.com/RustCrypto/traits) `digest` traits for the hashers in [sha3], 
+//!   so they can be used with any crate that is generic over `digest::Digest` or `digest::ExtendableOutput`
+//! - `rand_core`: implements the `rand_core` traits for the [random number generator](rng::ShakeRng) on SHAKE256
 //! 
 //! # Example
 //! ```
-//! use jisp_sha3::preprocessing::le_encoding;
+//! # #[cfg(feature = "alloc")] {
 //! use jisp_sha3::sha3::sha3_224;
-//! use jisp_sha3::printer::print_bytes_le;
+//! use jisp_sha3::printer::print_bytes_be;
 //! 
-//! let hex = le_encoding("abc");
-//! let hash = sha3_224(&hex);
-//! let res = print_bytes_le(&hash);
+//! let hash = sha3_224(b"abc");
+//! let res = print_bytes_be(&hash);
 //! 
 //! let expected = "e642824c 3f8cf24a d09234ee 7d3c766f c9a3a516 8d0c94ad 73b46fdf".to_owned();
 //! assert_eq!(res, expected);
+//! # }
 //! ```
 
+#![no_std]
+
+#[cfg(any(feature = "std", test))]
+extern crate std;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 pub mod preprocessing;
 pub mod sha3;
+pub mod const_sha3;
+#[cfg(feature = "alloc")]
+pub mod batch;
+pub mod keccak;
+#[cfg(feature = "alloc")]
+pub mod cshake;
+#[cfg(feature = "alloc")]
+pub mod kmac;
+pub mod hmac;
+pub mod hkdf;
+pub mod pbkdf2;
+pub mod expand_message;
+#[cfg(feature = "alloc")]
+pub mod cavp;
+#[cfg(feature = "alloc")]
+pub mod tuplehash;
+#[cfg(feature = "alloc")]
+pub mod parallelhash;
+pub mod turboshake;
+#[cfg(feature = "alloc")]
+pub mod kangarootwelve;
+#[cfg(feature = "alloc")]
 pub mod printer;
+pub mod spongewrap;
+pub mod rng;
+pub mod error;
+#[cfg(test)]
+mod test_helpers;
+
+#[cfg(feature = "digest")]
+mod rustcrypto;
 
 /// Various functions from the inner workings of the [SHA-3](crate::sha3) algorithm. 
 /// You do not need to consider these just to use this crate's hashing functionality.
 /// They are merely accessible for those interested.
 pub mod internals {
     pub mod sponge;
+    pub mod duplex;
     pub mod keccak;
     pub mod state;
+    pub mod verify;
 }
\ No newline at end of file
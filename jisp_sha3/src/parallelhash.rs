@@ -0,0 +1,183 @@
+//! ParallelHash128 and ParallelHash256 as defined in NIST SP 800-185, for hashing long messages on multiple threads. 
+//!
+//! The message is split into blocks of `b` bytes which are hashed independently of each other, 
+//! after which the results are combined with [cSHAKE](crate::cshake). 
+//! The blocks are divided over all available threads, the `_with_threads` functions give control over the number of threads 
+//! and running it with a single thread computes the same hash without spawning any threads. 
+//! Threads are only used with the `std` feature, without it all blocks are hashed on the calling thread. 
+//! The ParallelHashXOF variants have an output of arbitrary length, where `l` bits are returned
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::parallelhash::{parallelhash128, parallelhash128_with_threads};
+//!
+//! let m = vec![0xa3; 10_000];
+//! let hash = parallelhash128(&m, 1024, 256, b"");
+//! let reference = parallelhash128_with_threads(&m, 1024, 256, b"", 1);
+//!
+//! assert_eq!(hash, reference);
+//! ```
+use crate::cshake::{cshake128, cshake256};
+use crate::internals::sponge::keccak_c;
+use crate::preprocessing::{left_encode, right_encode};
+use alloc::{vec, vec::Vec};
+
+pub fn parallelhash128(x:&[u8], b:usize, l:usize, s:&[u8]) -> Vec<u8> {
+    parallelhash::<21>(x, b, l, s, false, available_threads())
+}
+
+pub fn parallelhash256(x:&[u8], b:usize, l:usize, s:&[u8]) -> Vec<u8> {
+    parallelhash::<17>(x, b, l, s, false, available_threads())
+}
+
+pub fn parallelhashxof128(x:&[u8], b:usize, l:usize, s:&[u8]) -> Vec<u8> {
+    parallelhash::<21>(x, b, l, s, true, available_threads())
+}
+
+pub fn parallelhashxof256(x:&[u8], b:usize, l:usize, s:&[u8]) -> Vec<u8> {
+    parallelhash::<17>(x, b, l, s, true, available_threads())
+}
+
+/// [parallelhash128] with the blocks divided over `threads` threads. The output does not depend on the number of threads, 
+/// with a single thread all blocks are hashed one after another on the calling thread
+///
+/// # Panics
+/// If the block size `b` or the number of threads is 0
+pub fn parallelhash128_with_threads(x:&[u8], b:usize, l:usize, s:&[u8], threads:usize) -> Vec<u8> {
+    parallelhash::<21>(x, b, l, s, false, threads)
+}
+
+/// [parallelhash256] with the blocks divided over `threads` threads, see [parallelhash128_with_threads]
+///
+/// # Panics
+/// If the block size `b` or the number of threads is 0
+pub fn parallelhash256_with_threads(x:&[u8], b:usize, l:usize, s:&[u8], threads:usize) -> Vec<u8> {
+    parallelhash::<17>(x, b, l, s, false, threads)
+}
+
+/// [parallelhashxof128] with the blocks divided over `threads` threads, see [parallelhash128_with_threads]
+///
+/// # Panics
+/// If the block size `b` or the number of threads is 0
+pub fn parallelhashxof128_with_threads(x:&[u8], b:usize, l:usize, s:&[u8], threads:usize) -> Vec<u8> {
+    parallelhash::<21>(x, b, l, s, true, threads)
+}
+
+/// [parallelhashxof256] with the blocks divided over `threads` threads, see [parallelhash128_with_threads]
+///
+/// # Panics
+/// If the block size `b` or the number of threads is 0
+pub fn parallelhashxof256_with_threads(x:&[u8], b:usize, l:usize, s:&[u8], threads:usize) -> Vec<u8> {
+    parallelhash::<17>(x, b, l, s, true, threads)
+}
+
+/// ParallelHash on `threads` threads, where `RATE` is 21 for ParallelHash128 and 17 for ParallelHash256. 
+/// Set `xof` to get the ParallelHashXOF variant
+fn parallelhash<const RATE:usize>(x:&[u8], b:usize, l:usize, s:&[u8], xof:bool, threads:usize) -> Vec<u8> {
+    if b == 0 {panic!("The block size of ParallelHash can not be 0!")}
+    if threads == 0 {panic!("ParallelHash needs at least 1 thread!")}
+
+    let n = x.len().div_ceil(b);
+    let mut z = left_encode(b);
+    z.extend(leaf_hashes::<RATE>(x, b, threads));
+    z.extend(right_encode(n));
+    z.extend(right_encode(if xof {0} else {l}));
+
+    let name:&[u8] = if xof {b"ParallelHashXOF"} else {b"ParallelHash"};
+    if RATE == 21 {
+        cshake128(&z, l, name, s)
+    } else {
+        cshake256(&z, l, name, s)
+    }
+}
+
+/// Hashes every block of `b` bytes to `c` bits, the capacity of the sponge. 
+/// Every thread gets a consecutive range of blocks and writes its results to its own part of the output
+#[cfg_attr(not(feature = "std"), allow(unused_variables))]
+fn leaf_hashes<const RATE:usize>(x:&[u8], b:usize, threads:usize) -> Vec<u8> {
+    let leaf_size = 200 - RATE*8;
+    let n = x.len().div_ceil(b);
+    let mut res = vec![0u8; n*leaf_size];
+    if n == 0 {
+        return res;
+    }
+
+    let hash_blocks = |blocks:&[u8], out:&mut [u8]| {
+        // cSHAKE with an empty name and customization string is SHAKE
+        let suffix = vec![true;4];
+        for (block, leaf) in blocks.chunks(b).zip(out.chunks_mut(leaf_size)) {
+            leaf.copy_from_slice(&keccak_c::<RATE>(block, &suffix, leaf_size*8));
+        }
+    };
+
+    #[cfg(feature = "std")]
+    if threads > 1 {
+        let blocks_per_thread = n.div_ceil(threads);
+        std::thread::scope(|scope| {
+            let inputs = x.chunks(blocks_per_thread*b);
+            let outputs = res.chunks_mut(blocks_per_thread*leaf_size);
+            for (blocks, out) in inputs.zip(outputs) {
+                scope.spawn(move || hash_blocks(blocks, out));
+            }
+        });
+        return res;
+    }
+
+    hash_blocks(x, &mut res);
+    res
+}
+
+#[cfg(feature = "std")]
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+#[cfg(not(feature = "std"))]
+fn available_threads() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::to_hex;
+
+    // The samples published by NIST for SP 800-185
+    #[test]
+    fn nist_samples() {
+        let x:Vec<u8> = (0..3).flat_map(|i| (0..8).map(move |j| 16*i + j)).collect();
+        let s = b"Parallel Data";
+
+        assert_eq!(to_hex(&parallelhash128(&x, 8, 256, b"")), "ba8dc1d1d979331d3f813603c67f72609ab5e44b94a0b8f9af46514454a2b4f5");
+        assert_eq!(to_hex(&parallelhash128(&x, 8, 256, s)), "fc484dcb3f84dceedc353438151bee58157d6efed0445a81f165e495795b7206");
+        assert_eq!(to_hex(&parallelhash256(&x, 8, 512, b"")), 
+            "bc1ef124da34495e948ead207dd9842235da432d2bbc54b4c110e64c451105531b7f2a3e0ce055c02805e7c2de1fb746af97a1dd01f43b824e31b87612410429");
+        assert_eq!(to_hex(&parallelhash256(&x, 8, 512, s)), 
+            "cdf15289b54f6212b4bc270528b49526006dd9b54e2b6add1ef6900dda3963bb33a72491f236969ca8afaea29c682d47a393c065b38e29fae651a2091c833110");
+
+        assert_eq!(to_hex(&parallelhashxof128(&x, 8, 256, b"")), "e2489b35c5db6e2dbb452f6278dd0324dc960490a8995f14da2062917639a876");
+        assert_eq!(to_hex(&parallelhashxof256(&x, 8, 512, s)), 
+            "5a3d82a94034b2a39ea6925efd48427a9e50c91ce6b5bb187b9c5964c1aa6835acc5cf0dc5df107f1e259d9ac1294d87a97317a534b5c8880609c7db1e31af87");
+    }
+
+    #[test]
+    fn threads_match_single_thread() {
+        // the final block is shorter than the others
+        let x:Vec<u8> = (0..250).collect();
+        let expected = parallelhash128_with_threads(&x, 7, 256, b"", 1);
+        assert_eq!(to_hex(&expected), "8b3dede75a17884d6e9942bed6cb4b92f56a55d01da099f450265b4464a8910a");
+
+        for threads in [2, 3, 8, 36, 100] {
+            assert_eq!(parallelhash128_with_threads(&x, 7, 256, b"", threads), expected);
+        }
+
+        let expected = parallelhashxof256_with_threads(&x, 16, 1000, b"s", 1);
+        assert_eq!(parallelhashxof256_with_threads(&x, 16, 1000, b"s", 5), expected);
+    }
+
+    #[test]
+    fn empty_message() {
+        let expected = parallelhash128_with_threads(&[], 8, 256, b"", 1);
+        assert_eq!(parallelhash128(&[], 8, 256, b""), expected);
+    }
+}
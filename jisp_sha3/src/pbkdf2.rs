@@ -0,0 +1,193 @@
+//! PBKDF2 as defined in RFC 8018, with [HMAC](crate::hmac) over one of the SHA-3 functions as the pseudorandom function.
+//!
+//! The key is derived from a password and a salt by running the pseudorandom function `iterations` times for every block of output,
+//! so a higher iteration count makes guessing passwords slower. The output length is given by the size of the buffer.
+//! The blocks of the output do not depend on each other, so they are divided over all available threads,
+//! [pbkdf2_with_threads] gives control over the number of threads.
+//! Threads are only used with the `std` feature, without it all blocks are computed on the calling thread
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::pbkdf2::{pbkdf2_hmac_sha3_256, pbkdf2_with_threads};
+//!
+//! let mut key = [0u8; 64];
+//! pbkdf2_hmac_sha3_256(b"password", b"salt", 1000, &mut key);
+//!
+//! let mut reference = [0u8; 64];
+//! pbkdf2_with_threads::<17, 256>(b"password", b"salt", 1000, &mut reference, 1);
+//! assert_eq!(key, reference);
+//! ```
+use crate::hmac::Hmac;
+
+/// # Panics
+/// If the number of iterations is 0 or the output is longer than `2^32 - 1` blocks of the digest
+pub fn pbkdf2_hmac_sha3_224(password:&[u8], salt:&[u8], iterations:u32, out:&mut [u8]) {
+    pbkdf2_with_threads::<18, 224>(password, salt, iterations, out, available_threads());
+}
+
+/// # Panics
+/// If the number of iterations is 0 or the output is longer than `2^32 - 1` blocks of the digest
+pub fn pbkdf2_hmac_sha3_256(password:&[u8], salt:&[u8], iterations:u32, out:&mut [u8]) {
+    pbkdf2_with_threads::<17, 256>(password, salt, iterations, out, available_threads());
+}
+
+/// # Panics
+/// If the number of iterations is 0 or the output is longer than `2^32 - 1` blocks of the digest
+pub fn pbkdf2_hmac_sha3_384(password:&[u8], salt:&[u8], iterations:u32, out:&mut [u8]) {
+    pbkdf2_with_threads::<13, 384>(password, salt, iterations, out, available_threads());
+}
+
+/// # Panics
+/// If the number of iterations is 0 or the output is longer than `2^32 - 1` blocks of the digest
+pub fn pbkdf2_hmac_sha3_512(password:&[u8], salt:&[u8], iterations:u32, out:&mut [u8]) {
+    pbkdf2_with_threads::<9, 512>(password, salt, iterations, out, available_threads());
+}
+
+/// PBKDF2 with HMAC over [Sha3<RATE, DIGEST>](crate::sha3::Sha3), with the blocks of the output divided over `threads` threads.
+/// The output does not depend on the number of threads, with a single thread all blocks are computed one after another on the calling thread
+///
+/// # Panics
+/// If the number of iterations or the number of threads is 0, 
+/// or if the output is longer than `2^32 - 1` blocks of the digest, the limit of RFC 8018
+#[cfg_attr(not(feature = "std"), allow(unused_variables))]
+pub fn pbkdf2_with_threads<const RATE:usize, const DIGEST:usize>(password:&[u8], salt:&[u8], iterations:u32, out:&mut [u8], threads:usize) {
+    if iterations == 0 {panic!("PBKDF2 needs at least 1 iteration!")}
+    if threads == 0 {panic!("PBKDF2 needs at least 1 thread!")}
+
+    let len = DIGEST / 8;
+    let n = out.len().div_ceil(len);
+    if n == 0 {
+        return;
+    }
+    if n > u32::MAX as usize {panic!("The derived key is too long for PBKDF2!")}
+
+    //the key is only processed once, every call of the PRF starts from a copy
+    let prf = Hmac::<RATE, DIGEST>::new(password);
+    let derive_blocks = |first:usize, out:&mut [u8]| {
+        for (i, block) in out.chunks_mut(len).enumerate() {
+            derive_block(&prf, salt, iterations, u32::try_from(first + i + 1).unwrap(), block);
+        }
+    };
+
+    #[cfg(feature = "std")]
+    if threads > 1 {
+        let blocks_per_thread = n.div_ceil(threads);
+        std::thread::scope(|scope| {
+            for (i, part) in out.chunks_mut(blocks_per_thread*len).enumerate() {
+                scope.spawn(move || derive_blocks(i*blocks_per_thread, part));
+            }
+        });
+        return;
+    }
+
+    derive_blocks(0, out);
+}
+
+/// `F(P, S, c, i) = U_1 ^ U_2 ^ ... ^ U_c` with `U_1 = PRF(P, S || INT(i))` and `U_j = PRF(P, U_{j-1})`,
+/// only the first `block.len()` bytes are written
+fn derive_block<const RATE:usize, const DIGEST:usize>(prf:&Hmac<RATE, DIGEST>, salt:&[u8], iterations:u32, i:u32, block:&mut [u8]) {
+    let len = DIGEST / 8;
+    let mut u = [0u8;64];
+    let mut t = [0u8;64];
+
+    let mut mac = prf.clone();
+    mac.update(salt);
+    mac.update(&i.to_be_bytes());
+    mac.finalize_into(&mut u[..len]);
+    t[..len].copy_from_slice(&u[..len]);
+
+    for _ in 1..iterations {
+        let mut mac = prf.clone();
+        mac.update(&u[..len]);
+        mac.finalize_into(&mut u[..len]);
+        for j in 0..len {
+            t[j] ^= u[j];
+        }
+    }
+    block.copy_from_slice(&t[..block.len()]);
+}
+
+#[cfg(feature = "std")]
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+#[cfg(not(feature = "std"))]
+fn available_threads() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::from_hex;
+
+    /// Runs the inputs of the test vectors of RFC 6070 through `f`, which fills a buffer of the given length
+    fn check(f:impl Fn(&[u8], &[u8], u32, &mut [u8]), digest:usize, expected:[&str;5]) {
+        let inputs:[(&[u8], &[u8], u32, usize);5] = [
+            (b"password", b"salt", 1, digest),
+            (b"password", b"salt", 2, digest),
+            (b"password", b"salt", 4096, digest),
+            (b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, 100),
+            (b"pass\0word", b"sa\0lt", 4096, 16),
+        ];
+        for ((password, salt, iterations, len), expected) in inputs.into_iter().zip(expected) {
+            let mut out = [0u8;100];
+            f(password, salt, iterations, &mut out[..len]);
+            assert_eq!(out[..len], from_hex(expected));
+        }
+    }
+
+    // Computed with Python's hashlib.pbkdf2_hmac
+    #[test]
+    fn known_answers() {
+        check(pbkdf2_hmac_sha3_224, 28, [
+            "d36cad0feea8cf942860130463093a623bead21f82366f184f318b4f",
+            "7979d7e05025f5b056e995939694ad55c644f43f1f748895c4ca8811",
+            "691292bc3683d7d41ea2910f5b3eed239d5fec2c84606dfb136934b8",
+            "00340fae2d7b57642248fde4835852cbbaaa865726550617ba6fb4bfede7e129bb916ae3ee9e7ff5b9086a4826a49242af8fc528471e580905d9623e82c6928cb3da7bd341f64aa4a6057b7545ff22f916d7d0ef7302b4f1665812fbaf1b36eac042b8b2",
+            "2cc83faf3bebbff68ead65c7cb2e0b16",
+        ]);
+        check(pbkdf2_hmac_sha3_256, 32, [
+            "94613f3ee2ea730e0b06754f3fc816d4f87c9be9cbd8556b5d59b52330e333a8",
+            "4c915baedd1773383e77fcfe38114ca7514010adec24b47290ec170208423f76",
+            "778b6e237a0f49621549ff70d218d2080756b9fb38d71b5d7ef447fa2254af61",
+            "7aef8f1ad8c7f12205334f624d4af9e2863121618f7a0b3209bef3934801c39feac24ef0ac6a5c252eb5a977f4036f5b04193036c24a6e5d32ba267f2dc5e3f6ae39d0ec7baebec6a99c890fb89844388b5bc8e098ec0aedeaa93b79b853c43a1de0fcd2",
+            "98e5503130ffdd69603da78cbb12e9be",
+        ]);
+        check(pbkdf2_hmac_sha3_384, 48, [
+            "7d7aba341e6ac84e9938f0f5a2f63c07daa3e0584cc6db99650a75eb2948f2b9591f591c805c9e918a2755fef7b7a3b0",
+            "0a163df94a9e97f05fb5bf609c4b02854299dc46099e115fad31c00656b317b88e1029d68aef38d974c521f933553781",
+            "9a5f1e45e8b83f1b259ba72d11c5908701b8678b86f01d81196771818e614d01797d3d5ac440435f00209cae8723c58c",
+            "dd3bb4762dd90da99ea1c0571a2b40dd00761fa70a4ab85d47fc07487564e0f46dfa4d102c3f8ca331f918a6ba602792fdb08a201da753253dcafdca3ece66736cbdaf4f2ba0d030d7cf8d66013d9dd557ea50ea991bc96a18d8421fe689f74a03f71ba8",
+            "b4b0457008f85b370cf79c3a80cc4496",
+        ]);
+        check(pbkdf2_hmac_sha3_512, 64, [
+            "f7a2684630ec0f81f23abbf606278deeaad1a35053db3c066903d9114ed3fd6e44c23dd5bddbe4e81626880cef267ef7dcf13b183194a5530f154ec57f646e2d",
+            "d6824ab17801706ad465f3196eb80dde20378696ab1fd6c68345c35825657a76954a1b24c7bc6589def0fe3eafae8b657c590bf0f065620b32d4d5450c8d2ee1",
+            "2bfaf2d5ceb6d10f5e262cd902488cfd4489614ecd6709e5ee395dc33f2e9ad7f89d31ad6781e90940e9e534ff44b817159ddcd3bdce3373541186b727340231",
+            "d60791a4ed27195d813f35510351b9d1ff9ad426215394460950a4fe03dd9f548710e552615ab127aa6b96d923a9e65a64a8332886cb024fa4e7d6ca3456c22ed912f6c81befcc67152d00ae25f12aee3684edb7621e88d3da50158c799b66592023950f",
+            "c0da8018507821037c76801cccf3cc8a",
+        ]);
+    }
+
+    #[test]
+    fn threads_match_single_thread() {
+        let mut expected = [0u8;300];
+        pbkdf2_with_threads::<9, 512>(b"password", b"salt", 10, &mut expected, 1);
+
+        for threads in [2, 3, 5, 8, 100] {
+            let mut out = [0u8;300];
+            pbkdf2_with_threads::<9, 512>(b"password", b"salt", 10, &mut out, threads);
+            assert_eq!(out, expected);
+        }
+
+        pbkdf2_with_threads::<9, 512>(b"password", b"salt", 10, &mut [], 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_iterations() {
+        pbkdf2_hmac_sha3_256(b"password", b"salt", 0, &mut [0u8;32]);
+    }
+}
@@ -1,21 +1,260 @@
 //! A collection of functions used for message encoding and padding
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
 
-/// flips each individual byte in a vector from little endian ordering to big endian ordering or vice versa
+/// flips each individual byte in a vector from little endian ordering to big endian ordering or vice versa. 
+/// Only needed for the functions in [legacy](crate::sha3::legacy), the standard functions take the bytes as they are
+#[cfg(feature = "alloc")]
+#[allow(clippy::ptr_arg)]
 pub fn flip_ordering(v: &Vec<u8>) -> Vec<u8> {
     v.iter().map(|u| u.reverse_bits()).collect()
 }
 
 /// encodes a string into bytes using little endian byte encoding
+#[cfg(feature = "alloc")]
 pub fn le_encoding(s: &str) -> Vec<u8> {
     let bytes = be_encoding(s);
     flip_ordering(&bytes)
 }
 
 /// encodes a string into bytes using big endian byte encoding
+#[cfg(feature = "alloc")]
 pub fn be_encoding(s: &str) ->Vec<u8>{
     s.as_bytes().into()
 }
 
+/// A string of bits of any length, as used in FIPS 202 to define SHA-3 on messages that do not fill a whole number of bytes. 
+/// Bit `i` of the string is stored in byte `i / 8` at position `i % 8`, counting from the least significant bit. 
+/// This means the 5 bit message `11001` is stored as the byte `0x13`, just like in the NIST examples
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::BitString;
+///
+/// let bits = BitString::from_bits(&[true, true, false, false, true]);
+/// assert_eq!(bits, BitString::from_bytes(&[0x13], 5));
+/// assert_eq!(bits.as_bytes(), &[0x13]);
+/// assert_eq!(bits.len(), 5);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitString {
+    bytes:Vec<u8>,
+    len:usize,
+}
+
+#[cfg(feature = "alloc")]
+impl BitString {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the first `len` bits from `bytes`, any bits after that are ignored
+    ///
+    /// # Panics
+    /// If `bytes` contains fewer than `len` bits
+    pub fn from_bytes(bytes:&[u8], len:usize) -> Self {
+        if len > bytes.len()*8 {panic!("Not enough bytes for a bit string of {} bits!", len)}
+        let mut bytes = bytes[..len.div_ceil(8)].to_vec();
+        if !len.is_multiple_of(8) {
+            let last = bytes.len() - 1;
+            bytes[last] &= (1 << (len % 8)) - 1;
+        }
+        BitString { bytes, len }
+    }
+
+    pub fn from_bits(bits:&[bool]) -> Self {
+        let mut res = Self::new();
+        for bit in bits {
+            res.push(*bit);
+        }
+        res
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// # Panics
+    /// If `index` is not smaller than the length of the string
+    pub fn get(&self, index:usize) -> bool {
+        if index >= self.len {panic!("Bit {} is out of range for a bit string of {} bits!", index, self.len)}
+        (self.bytes[index / 8] >> (index % 8)) & 1 == 1
+    }
+
+    pub fn push(&mut self, bit:bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        self.bytes[self.len / 8] |= (bit as u8) << (self.len % 8);
+        self.len += 1;
+    }
+
+    /// The bits packed into bytes, unused bits of the final byte are set to 0
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Converts a hexadecimal string of bytes into a bit string of `n` bits, following Algorithm 10 (h2b) of FIPS 202 Appendix B.1. 
+/// The bits of every byte are taken starting at the least significant bit
+///
+/// # Panics
+/// If `bytes` contains fewer than `n` bits
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::h2b;
+///
+/// let bits = h2b(&[0xa3], 8);
+/// assert!(bits.get(0) && bits.get(1) && bits.get(5) && bits.get(7));
+/// assert!(!bits.get(2));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn h2b(bytes:&[u8], n:usize) -> BitString {
+    BitString::from_bytes(bytes, n)
+}
+
+/// Converts a bit string back into bytes, following Algorithm 11 (b2h) of FIPS 202 Appendix B.1. 
+/// The string is padded with zeros up to a whole number of bytes
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::{h2b, b2h};
+///
+/// let bits = h2b(&[0xff, 0xff], 12);
+/// assert_eq!(b2h(&bits), vec![0xff, 0x0f]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn b2h(s:&BitString) -> Vec<u8> {
+    s.as_bytes().to_vec()
+}
+
+/// Turns 8 bytes into a word of the state. This is [h2b] on the level of a whole lane: 
+/// the first bit of the bit string ends up in the most significant bit of the word
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::bytes_to_lane;
+///
+/// assert_eq!(bytes_to_lane([0x01, 0, 0, 0, 0, 0, 0, 0x80]), 1 << 63 | 1);
+/// ```
+pub const fn bytes_to_lane(bytes:[u8;8]) -> u64 {
+    u64::from_le_bytes(bytes).reverse_bits()
+}
+
+/// The inverse of [bytes_to_lane], turning a word of the state back into bytes like [b2h]
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::lane_to_bytes;
+///
+/// assert_eq!(lane_to_bytes(1 << 63 | 1), [0x01, 0, 0, 0, 0, 0, 0, 0x80]);
+/// ```
+pub const fn lane_to_bytes(lane:u64) -> [u8;8] {
+    lane.reverse_bits().to_le_bytes()
+}
+
+/// Encodes an integer as a byte string that can be parsed from the left, as defined in NIST SP 800-185. 
+/// The first byte contains the number of bytes needed for `x`, followed by `x` itself in big endian order
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::left_encode;
+///
+/// assert_eq!(left_encode(0), vec![1, 0]);
+/// assert_eq!(left_encode(168), vec![1, 168]);
+/// assert_eq!(left_encode(256), vec![2, 1, 0]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn left_encode(x:usize) -> Vec<u8> {
+    let mut res = right_encode(x);
+    res.rotate_right(1);
+    res
+}
+
+/// Encodes an integer as a byte string that can be parsed from the right, as defined in NIST SP 800-185. 
+/// This is `x` in big endian order followed by the number of bytes needed for it
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::right_encode;
+///
+/// assert_eq!(right_encode(0), vec![0, 1]);
+/// assert_eq!(right_encode(256), vec![1, 0, 2]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn right_encode(x:usize) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let n = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len() - 1);
+
+    let mut res = bytes[n..].to_vec();
+    res.push(res.len() as u8);
+    res
+}
+
+/// Encodes a byte string so that it can be unambiguously parsed from the start of a longer string, as defined in NIST SP 800-185. 
+/// It is prefixed by its length in bits using [left_encode]
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::encode_string;
+///
+/// assert_eq!(encode_string(b""), vec![1, 0]);
+/// assert_eq!(encode_string(b"abc"), vec![1, 24, b'a', b'b', b'c']);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn encode_string(s:&[u8]) -> Vec<u8> {
+    let mut res = left_encode(s.len()*8);
+    res.extend_from_slice(s);
+    res
+}
+
+/// Prepends [left_encode] of `w` to `x` and pads the result with zeros to a multiple of `w` bytes, as defined in NIST SP 800-185
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::bytepad;
+///
+/// assert_eq!(bytepad(&[0xff], 4), vec![1, 4, 0xff, 0]);
+/// assert_eq!(bytepad(&[0xff; 3], 4), vec![1, 4, 0xff, 0xff, 0xff, 0, 0, 0]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn bytepad(x:&[u8], w:usize) -> Vec<u8> {
+    let mut res = left_encode(w);
+    res.extend_from_slice(x);
+    while !res.len().is_multiple_of(w) {
+        res.push(0);
+    }
+    res
+}
+
+/// Encodes an integer for KangarooTwelve as defined in RFC 9861. 
+/// This is `x` in big endian order without leading zeros, followed by the number of bytes used. 
+/// Note that unlike [right_encode] the number 0 is encoded without any bytes
+///
+/// # Examples
+/// ```
+/// use jisp_sha3::preprocessing::length_encode;
+///
+/// assert_eq!(length_encode(0), vec![0]);
+/// assert_eq!(length_encode(12), vec![12, 1]);
+/// assert_eq!(length_encode(65538), vec![1, 0, 2, 3]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn length_encode(x:usize) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let n = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+
+    let mut res = bytes[n..].to_vec();
+    res.push(res.len() as u8);
+    res
+}
+
 /// Splits `u64` words into `u8` bytes. Used internally to transform words in a state back into bytes
 /// # Examples
 /// ```
@@ -25,6 +264,8 @@ pub fn be_encoding(s: &str) ->Vec<u8>{
 /// 
 /// assert_eq!(bytes, vec![1, 2, 3, 4, 5, 6, 7, 8]);
 /// ```
+#[cfg(feature = "alloc")]
+#[allow(clippy::needless_return)]
 pub fn split_bytes(v:&Vec<u64>) -> Vec<u8> {
     let mut res = Vec::new();
     for word in v {
@@ -41,7 +282,8 @@ pub fn split_bytes(v:&Vec<u64>) -> Vec<u8> {
     return res;
 }
 
-/// Pads a string of bytes and splits it in the specified block-size. Used internally in the [SHA-3](crate::sha3) functions
+/// Pads a string of bytes and splits it in the specified block-size. Used internally in the [legacy](crate::sha3::legacy) SHA-3 functions, 
+/// every byte is read starting at its most significant bit
 /// 
 /// # Panics
 /// If the suffix is longer than 6 bits. Note that it is a maximum of 4 bits in the 
@@ -58,6 +300,8 @@ pub fn split_bytes(v:&Vec<u64>) -> Vec<u8> {
 /// 
 /// assert_eq!(res, expected);
 /// ```
+#[cfg(feature = "alloc")]
+#[allow(clippy::let_and_return)]
 pub fn padding<const BLOCK:usize>(bytes:&Vec<u8>, suffix:&Vec<bool>) -> Vec<[u64; BLOCK]> {
     vec![0;7];
     let words = merge_bytes(bytes, suffix);
@@ -65,6 +309,47 @@ pub fn padding<const BLOCK:usize>(bytes:&Vec<u8>, suffix:&Vec<bool>) -> Vec<[u64
     blocks
 }
 
+/// Joins a bit string, the suffix and the pad10*1 padding and splits them in the specified block-size. 
+/// Unlike [padding] the message can have any length, used internally in the [SHA-3](crate::sha3) functions
+///
+/// # Example
+/// ```
+/// use jisp_sha3::preprocessing::{padding_bits, BitString};
+///
+/// let m = BitString::from_bits(&[true, true, false, false, true]);
+/// let suffix = vec![false, true];
+///
+/// let res = padding_bits::<1>(&m, &suffix)[0][0];
+/// let expected:u64 = 0b1100_1011 << 56 | 1;
+///
+/// assert_eq!(res, expected);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn padding_bits<const BLOCK:usize>(m:&BitString, suffix:&Vec<bool>) -> Vec<[u64; BLOCK]> {
+    let mut bits = m.clone();
+    for b in suffix {
+        bits.push(*b);
+    }
+
+    //pad10*1
+    bits.push(true);
+    while !(bits.len() + 1).is_multiple_of(64*BLOCK) {
+        bits.push(false);
+    }
+    bits.push(true);
+
+    //the first bit of a word is its most significant bit
+    let mut res = vec![[0u64;BLOCK]; bits.len() / (64*BLOCK)];
+    for i in 0..bits.len() {
+        if bits.get(i) {
+            res[i / (64*BLOCK)][(i / 64) % BLOCK] |= 1 << (63 - i % 64);
+        }
+    }
+    res
+}
+
+#[cfg(feature = "alloc")]
+#[allow(clippy::needless_return)]
 fn merge_words<const BLOCK:usize>(words: &Vec<u64> ) -> Vec<[u64;BLOCK]> {
     let mut res = Vec::new();
 
@@ -87,6 +372,8 @@ fn merge_words<const BLOCK:usize>(words: &Vec<u64> ) -> Vec<[u64;BLOCK]> {
     return res;
 }
 
+#[cfg(feature = "alloc")]
+#[allow(clippy::assign_op_pattern, clippy::needless_return)]
 fn merge_bytes(bytes: &Vec<u8>, suffix:&Vec<bool>) -> Vec<u64> {
     
     let mut res = Vec::new();
@@ -122,6 +409,8 @@ fn merge_bytes(bytes: &Vec<u8>, suffix:&Vec<bool>) -> Vec<u64> {
     return res;
 }
 
+#[cfg(feature = "alloc")]
+#[allow(clippy::assign_op_pattern, clippy::needless_return)]
 fn suffix_to_u8(suffix:&Vec<bool>) -> u8 {
     if suffix.len() > 6 {panic!("Suffix is longer than 6 bits!")}
     let mut res = 0;
@@ -1,6 +1,7 @@
 //! Some simple functions to transform states and bytes into strings
 
 use crate::preprocessing::flip_ordering;
+use alloc::{format, string::String, vec::Vec};
 
 /// prints bytes as hexadecimal values using big endian encoding, adds a space every 4 bytes
 /// 
@@ -12,6 +13,7 @@ use crate::preprocessing::flip_ordering;
 /// 
 /// assert_eq!(s, "01020304 05060708".to_owned());
 /// ```
+#[allow(clippy::needless_return)]
 pub fn print_bytes_be(v:&Vec<u8>) -> String {
     let mut res = String::from("");
     let mut n = 0;
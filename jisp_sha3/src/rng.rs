@@ -0,0 +1,210 @@
+//! A deterministic random bit generator on [SHAKE256](crate::sha3::Shake256), for reproducible randomness in simulations and tests.
+//!
+//! The output of a [ShakeRng] seeded with a byte string is exactly the output of SHAKE256 on that string,
+//! so the same seed gives the same numbers in every version of this crate. Integers are read from the output in little-endian order.
+//! With the `rand_core` feature it implements `rand_core::RngCore` and `rand_core::SeedableRng`,
+//! so it can be used with the `rand` crate
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::rng::ShakeRng;
+//!
+//! let mut rng = ShakeRng::from_seed_bytes(b"simulation 42");
+//! let roll = rng.gen_range(1..7);
+//! assert!((1..7).contains(&roll));
+//!
+//! let mut deck:[u8;52] = core::array::from_fn(|i| i as u8);
+//! rng.shuffle(&mut deck);
+//!
+//! // the same seed gives the same results
+//! let mut again = ShakeRng::from_seed_bytes(b"simulation 42");
+//! assert_eq!(again.gen_range(1..7), roll);
+//! ```
+use crate::internals::sponge::SpongeReader;
+use crate::sha3::Shake256;
+use core::ops::Range;
+
+/// The number of output bytes that are carried over into the new state by [reseed](ShakeRng::reseed)
+const RESEED_LEN:usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct ShakeRng {
+    reader:SpongeReader<17>,
+}
+
+impl ShakeRng {
+    /// A generator whose output is SHAKE256 of `seed`
+    pub fn from_seed_bytes(seed:&[u8]) -> Self {
+        let mut hasher = Shake256::new();
+        hasher.update(seed);
+        ShakeRng { reader: hasher.finalize_xof() }
+    }
+
+    /// Mixes `entropy` into the state. The new output is SHAKE256 of the next 64 bytes of output followed by `entropy`,
+    /// so it depends on both the old state and the new input
+    pub fn reseed(&mut self, entropy:&[u8]) {
+        let mut carry = [0u8;RESEED_LEN];
+        self.reader.read(&mut carry);
+
+        let mut hasher = Shake256::new();
+        hasher.update(&carry);
+        hasher.update(entropy);
+        self.reader = hasher.finalize_xof();
+    }
+
+    /// Fills `buffer` with the next bytes of output
+    pub fn fill_bytes(&mut self, buffer:&mut [u8]) {
+        self.reader.read(buffer);
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8;4];
+        self.reader.read(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8;8];
+        self.reader.read(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// A uniformly distributed integer in `0..n`.
+    /// Outputs from the incomplete interval at the top of the `u64` range are rejected, so every value is equally likely
+    ///
+    /// # Panics
+    /// If `n` is zero
+    pub fn below(&mut self, n:u64) -> u64 {
+        if n == 0 {panic!("Can not pick an integer below 0!")}
+
+        //2^64 mod n, the number of values that would make the result biased
+        let excess = (u64::MAX % n + 1) % n;
+        loop {
+            let x = self.next_u64();
+            if x <= u64::MAX - excess {
+                return x % n;
+            }
+        }
+    }
+
+    /// A uniformly distributed integer in `range`
+    ///
+    /// # Panics
+    /// If the range is empty
+    pub fn gen_range(&mut self, range:Range<u64>) -> u64 {
+        if range.is_empty() {panic!("Can not pick an integer from the empty range {:?}!", range)}
+        range.start + self.below(range.end - range.start)
+    }
+
+    /// Puts the elements of `slice` in a uniformly random order with the Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, slice:&mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            slice.swap(i, j);
+        }
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for ShakeRng {
+    fn next_u32(&mut self) -> u32 {
+        ShakeRng::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        ShakeRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest:&mut [u8]) {
+        ShakeRng::fill_bytes(self, dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest:&mut [u8]) -> Result<(), rand_core::Error> {
+        ShakeRng::fill_bytes(self, dest);
+        Ok(())
+    }
+}
+
+/// The seed is used as the input of SHAKE256, the same as [from_seed_bytes](ShakeRng::from_seed_bytes)
+#[cfg(feature = "rand_core")]
+impl rand_core::SeedableRng for ShakeRng {
+    type Seed = [u8;32];
+
+    fn from_seed(seed:Self::Seed) -> Self {
+        ShakeRng::from_seed_bytes(&seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The output bytes are those of Python's hashlib.shake_256
+    #[test]
+    fn output_is_shake256() {
+        let mut rng = ShakeRng::from_seed_bytes(b"jisp_sha3 rng");
+        let mut bytes = [0u8;16];
+        rng.fill_bytes(&mut bytes);
+        assert_eq!(bytes, [0x6f, 0x72, 0x34, 0xb1, 0x95, 0xcb, 0xf8, 0x93, 0xd5, 0x44, 0xd7, 0x78, 0x8b, 0x9b, 0x9b, 0x4d]);
+        assert_eq!(rng.next_u32(), 0xd87f802f);
+        assert_eq!(rng.next_u64(), 0xd3d726d548840f02);
+
+        // reading across the block boundary of 136 bytes
+        let mut rng = ShakeRng::from_seed_bytes(b"");
+        let mut bytes = [0u8;140];
+        rng.fill_bytes(&mut bytes);
+        assert_eq!(bytes[..4], [0x46, 0xb9, 0xdd, 0x2b]);
+        assert_eq!(bytes[132..], [0x9e, 0x3b, 0x1f, 0xdd, 0x94, 0x3b, 0x6a, 0xec]);
+    }
+
+    #[test]
+    fn reseed() {
+        let mut rng = ShakeRng::from_seed_bytes(b"jisp_sha3 rng");
+        rng.next_u64();
+        rng.reseed(b"more entropy");
+        assert_eq!(rng.next_u64(), 0x4324a163b87ded63);
+
+        // an empty reseed still changes the state
+        let mut a = ShakeRng::from_seed_bytes(b"seed");
+        let mut b = a.clone();
+        b.reseed(b"");
+        assert_ne!(a.next_u64(), b.next_u64());
+    }
+
+    // Computed with a separate Python implementation that reads the output of hashlib.shake_256,
+    // rejects values of at least the largest multiple of n below 2^64 and shuffles with the textbook Fisher-Yates loop
+    #[test]
+    fn ranges_and_shuffles() {
+        let mut rng = ShakeRng::from_seed_bytes(b"jisp_sha3 rng");
+        let rolls:[u64;10] = core::array::from_fn(|_| rng.gen_range(1..7));
+        assert_eq!(rolls, [2, 6, 2, 4, 3, 2, 6, 4, 6, 6]);
+
+        let mut deck:[u8;10] = core::array::from_fn(|i| i as u8);
+        rng.shuffle(&mut deck);
+        assert_eq!(deck, [5, 0, 2, 3, 6, 9, 8, 4, 1, 7]);
+
+        assert_eq!(rng.gen_range(5..6), 5);
+        assert!(rng.gen_range(0..u64::MAX) < u64::MAX);
+        assert!(rng.below(1 << 63 | 1) <= 1 << 63);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_range() {
+        ShakeRng::from_seed_bytes(b"").gen_range(3..3);
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core")]
+    fn rand_core_traits() {
+        use rand_core::{RngCore, SeedableRng};
+
+        let mut rng = ShakeRng::from_seed([7u8;32]);
+        let mut expected = ShakeRng::from_seed_bytes(&[7u8;32]);
+        assert_eq!(RngCore::next_u64(&mut rng), expected.next_u64());
+
+        // the seed of 32 bytes from the PCG32 generator of rand_core, recomputed in Python and hashed with hashlib.shake_256
+        let mut rng = ShakeRng::seed_from_u64(42);
+        assert_eq!(RngCore::next_u32(&mut rng), 0x832ec795);
+    }
+}
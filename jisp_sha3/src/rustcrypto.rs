@@ -0,0 +1,135 @@
+//! Implementations of the RustCrypto `digest` traits, enabled by the `digest` feature
+use digest::{ExtendableOutput, FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update, XofReader};
+use digest::core_api::BlockSizeUser;
+use digest::consts::{U28, U32, U48, U64, U72, U104, U136, U144};
+
+use crate::internals::sponge::SpongeReader;
+use crate::sha3::{Sha3, Shake};
+
+impl<const RATE:usize, const DIGEST:usize> HashMarker for Sha3<RATE, DIGEST> {}
+
+impl<const RATE:usize, const DIGEST:usize> Update for Sha3<RATE, DIGEST> {
+    fn update(&mut self, data:&[u8]) {
+        Sha3::update(self, data);
+    }
+}
+
+impl<const RATE:usize, const DIGEST:usize> Reset for Sha3<RATE, DIGEST> {
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// The output and block size have to be given as `typenum` types, so every variant gets its own implementation
+macro_rules! impl_fixed_output {
+    ($rate:literal, $digest:literal, $output_size:ty, $block_size:ty) => {
+        impl OutputSizeUser for Sha3<$rate, $digest> {
+            type OutputSize = $output_size;
+        }
+
+        impl BlockSizeUser for Sha3<$rate, $digest> {
+            type BlockSize = $block_size;
+        }
+
+        impl FixedOutput for Sha3<$rate, $digest> {
+            fn finalize_into(self, out:&mut Output<Self>) {
+                Sha3::finalize_into(self, out);
+            }
+        }
+
+        impl FixedOutputReset for Sha3<$rate, $digest> {
+            fn finalize_into_reset(&mut self, out:&mut Output<Self>) {
+                self.clone().finalize_into(out);
+                Reset::reset(self);
+            }
+        }
+    };
+}
+
+impl_fixed_output!(18, 224, U28, U144);
+impl_fixed_output!(17, 256, U32, U136);
+impl_fixed_output!(13, 384, U48, U104);
+impl_fixed_output!(9, 512, U64, U72);
+
+impl<const RATE:usize> Update for Shake<RATE> {
+    fn update(&mut self, data:&[u8]) {
+        Shake::update(self, data);
+    }
+}
+
+impl<const RATE:usize> Reset for Shake<RATE> {
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const RATE:usize> ExtendableOutput for Shake<RATE> {
+    type Reader = SpongeReader<RATE>;
+
+    fn finalize_xof(self) -> Self::Reader {
+        Shake::finalize_xof(self)
+    }
+}
+
+impl<const RATE:usize> XofReader for SpongeReader<RATE> {
+    fn read(&mut self, buffer:&mut [u8]) {
+        SpongeReader::read(self, buffer);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use digest::Digest;
+    use super::*;
+    use std::{vec, vec::Vec};
+    use crate::sha3::{self, Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256, unofficial_sha::Shake512};
+
+    fn generic_digest<D:Digest>(parts:&[&[u8]]) -> Vec<u8> {
+        let mut hasher = D::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().to_vec()
+    }
+
+    fn generic_xof<X:ExtendableOutput + Default>(m:&[u8], output:usize) -> Vec<u8> {
+        let mut hasher = X::default();
+        hasher.update(m);
+        let mut reader = hasher.finalize_xof();
+
+        let mut res = vec![0u8; output];
+        let (first, second) = res.split_at_mut(output / 3);
+        reader.read(first);
+        reader.read(second);
+        res
+    }
+
+    #[test]
+    fn digest_matches_sha3() {
+        let m:Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let parts = [&m[..10], &m[10..]];
+        assert_eq!(generic_digest::<Sha3_224>(&parts), sha3::sha3_224(&m));
+        assert_eq!(generic_digest::<Sha3_256>(&parts), sha3::sha3_256(&m));
+        assert_eq!(generic_digest::<Sha3_384>(&parts), sha3::sha3_384(&m));
+        assert_eq!(generic_digest::<Sha3_512>(&parts), sha3::sha3_512(&m));
+    }
+
+    #[test]
+    fn xof_matches_shake() {
+        let m = b"abc";
+        assert_eq!(generic_xof::<Shake128>(m, 300), sha3::shake128(m, 300*8));
+        assert_eq!(generic_xof::<Shake256>(m, 300), sha3::shake256(m, 300*8));
+        assert_eq!(generic_xof::<Shake512>(m, 300), sha3::unofficial_sha::shake512(m, 300*8));
+    }
+
+    #[test]
+    fn finalize_reset() {
+        let mut hasher = Sha3_256::new();
+        Update::update(&mut hasher, b"abc");
+        let first = hasher.finalize_fixed_reset();
+
+        Update::update(&mut hasher, b"abc");
+        assert_eq!(first, hasher.finalize_fixed());
+        assert_eq!(<Sha3_256 as BlockSizeUser>::block_size(), 136);
+    }
+}
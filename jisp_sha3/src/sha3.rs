@@ -1,45 +1,444 @@
-//! The functions in this module perform their respective variations of the SHA-3 algorithm and include message padding 
-use crate::internals::sponge::keccak_c;
+//! The functions in this module perform their respective variations of the SHA-3 algorithm and include message padding. 
+//! They take plain bytes and return the digest bytes as specified in FIPS 202, the same as any other SHA-3 implementation. 
+//! The functions using the original bit ordering of this crate can be found in [legacy]
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use jisp_sha3::sha3::sha3_256;
+//! use jisp_sha3::printer::print_bytes_be;
+//!
+//! let hash = sha3_256(b"abc");
+//! let expected = "3a985da7 4fe225b2 045c172d 6bd390bd 855f086e 3e9d525b 46bfe245 11431532";
+//! assert_eq!(print_bytes_be(&hash), expected);
+//! # }
+//! ```
+use crate::internals::sponge::{Sponge, SpongeReader};
+#[cfg(feature = "alloc")]
+use crate::internals::sponge::{keccak_c, keccak_c_bits};
+#[cfg(feature = "alloc")]
+use crate::preprocessing::BitString;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
 
-pub fn sha3_224(m:&Vec<u8>) -> Vec<u8> {
+#[cfg(feature = "alloc")]
+pub fn sha3_224(m:&[u8]) -> Vec<u8> {
     let suffix = vec![false, true];
     keccak_c::<18>(m, &suffix, 224)
 }
-pub fn sha3_256(m:&Vec<u8>) -> Vec<u8> {
+#[cfg(feature = "alloc")]
+pub fn sha3_256(m:&[u8]) -> Vec<u8> {
     let suffix = vec![false, true];
     keccak_c::<17>(m, &suffix, 256)
 }
-pub fn sha3_384(m:&Vec<u8>) -> Vec<u8> {
+#[cfg(feature = "alloc")]
+pub fn sha3_384(m:&[u8]) -> Vec<u8> {
     let suffix = vec![false, true];
     keccak_c::<13>(m, &suffix, 384)
 }
-pub fn sha3_512(m:&Vec<u8>) -> Vec<u8> {
+#[cfg(feature = "alloc")]
+pub fn sha3_512(m:&[u8]) -> Vec<u8> {
     let suffix = vec![false, true];
     keccak_c::<9>(m, &suffix, 512)
 }
 
-pub fn shake128(m:&Vec<u8>, output_length:usize) -> Vec<u8> {
+#[cfg(feature = "alloc")]
+pub fn shake128(m:&[u8], output_length:usize) -> Vec<u8> {
     let suffix = vec![true;4];
     keccak_c::<21>(m, &suffix, output_length)
 }
 
-pub fn shake256(m:&Vec<u8>, output_length:usize) -> Vec<u8> {
+#[cfg(feature = "alloc")]
+pub fn shake256(m:&[u8], output_length:usize) -> Vec<u8> {
     let suffix = vec![true;4];
     keccak_c::<17>(m, &suffix, output_length)
 }
 
+/// SHA3-224 on a message of any number of bits, see [BitString] for the ordering of the bits
+///
+/// # Example
+/// ```
+/// use jisp_sha3::sha3::sha3_224_bits;
+/// use jisp_sha3::preprocessing::BitString;
+///
+/// let m = BitString::from_bits(&[true, true, false, false, true]);
+/// let hash = sha3_224_bits(&m);
+///
+/// assert_eq!(hash.len(), 224);
+/// assert_eq!(hash.as_bytes()[..4], [0xff, 0xba, 0xd5, 0xda]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn sha3_224_bits(m:&BitString) -> BitString {
+    let suffix = vec![false, true];
+    keccak_c_bits::<18>(m, &suffix, 224)
+}
+#[cfg(feature = "alloc")]
+pub fn sha3_256_bits(m:&BitString) -> BitString {
+    let suffix = vec![false, true];
+    keccak_c_bits::<17>(m, &suffix, 256)
+}
+#[cfg(feature = "alloc")]
+pub fn sha3_384_bits(m:&BitString) -> BitString {
+    let suffix = vec![false, true];
+    keccak_c_bits::<13>(m, &suffix, 384)
+}
+#[cfg(feature = "alloc")]
+pub fn sha3_512_bits(m:&BitString) -> BitString {
+    let suffix = vec![false, true];
+    keccak_c_bits::<9>(m, &suffix, 512)
+}
+
+#[cfg(feature = "alloc")]
+pub fn shake128_bits(m:&BitString, output_length:usize) -> BitString {
+    let suffix = vec![true;4];
+    keccak_c_bits::<21>(m, &suffix, output_length)
+}
+
+#[cfg(feature = "alloc")]
+pub fn shake256_bits(m:&BitString, output_length:usize) -> BitString {
+    let suffix = vec![true;4];
+    keccak_c_bits::<17>(m, &suffix, output_length)
+}
+
+/// Incremental version of the SHA-3 functions for messages that arrive in parts, 
+/// use one of the aliases [Sha3_224], [Sha3_256], [Sha3_384] or [Sha3_512]
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use jisp_sha3::sha3::{Sha3_256, sha3_256};
+///
+/// let mut hasher = Sha3_256::new();
+/// hasher.update(&[1, 2, 3]);
+/// hasher.update(&[4, 5]);
+///
+/// assert_eq!(hasher.finalize(), sha3_256(&[1, 2, 3, 4, 5]));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sha3<const RATE:usize, const DIGEST:usize> {
+    sponge:Sponge<RATE>,
+}
+
+pub type Sha3_224 = Sha3<18, 224>;
+pub type Sha3_256 = Sha3<17, 256>;
+pub type Sha3_384 = Sha3<13, 384>;
+pub type Sha3_512 = Sha3<9, 512>;
+
+impl<const RATE:usize, const DIGEST:usize> Sha3<RATE, DIGEST> {
+    pub fn new() -> Self {
+        Sha3 { sponge: Sponge::new(&[false, true]) }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        self.sponge.absorb(m);
+    }
+
+    /// Finishes the message and returns the `DIGEST` bit hash
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self) -> Vec<u8> {
+        self.sponge.squeeze(DIGEST)
+    }
+
+    /// Finishes the message and writes the `DIGEST` bit hash to `out`, without allocating
+    ///
+    /// # Panics
+    /// If `out` is not `DIGEST / 8` bytes long
+    ///
+    /// # Example
+    /// ```
+    /// use jisp_sha3::sha3::Sha3_224;
+    ///
+    /// let mut hasher = Sha3_224::new();
+    /// hasher.update(b"abc");
+    /// let mut hash = [0u8; 28];
+    /// hasher.finalize_into(&mut hash);
+    ///
+    /// assert_eq!(hash[..4], [0xe6, 0x42, 0x82, 0x4c]);
+    /// ```
+    pub fn finalize_into(self, out:&mut [u8]) {
+        if out.len() != DIGEST / 8 {panic!("A buffer of {} bytes does not fit a hash of {} bits!", out.len(), DIGEST)}
+        self.sponge.finalize().read(out);
+    }
+}
+
+impl<const RATE:usize, const DIGEST:usize> Default for Sha3<RATE, DIGEST> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Incremental version of the SHAKE functions, use one of the aliases [Shake128] or [Shake256]. 
+/// Next to a fixed length output it can also produce a [SpongeReader] to squeeze out bytes on demand.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use jisp_sha3::sha3::{Shake128, shake128};
+///
+/// let mut hasher = Shake128::new();
+/// hasher.update(&[1, 2, 3]);
+/// let mut reader = hasher.finalize_xof();
+///
+/// let mut key = [0u8; 16];
+/// let mut mask = [0u8; 48];
+/// reader.read(&mut key);
+/// reader.read(&mut mask);
+///
+/// assert_eq!([key.to_vec(), mask.to_vec()].concat(), shake128(&[1, 2, 3], 64*8));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Shake<const RATE:usize> {
+    sponge:Sponge<RATE>,
+}
+
+pub type Shake128 = Shake<21>;
+pub type Shake256 = Shake<17>;
+
+impl<const RATE:usize> Shake<RATE> {
+    pub fn new() -> Self {
+        Shake { sponge: Sponge::new(&[true;4]) }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        self.sponge.absorb(m);
+    }
+
+    /// Finishes the message and returns `output_length` bits of output
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self, output_length:usize) -> Vec<u8> {
+        self.sponge.squeeze(output_length)
+    }
+
+    /// Finishes the message and returns a reader for an output of unknown length
+    pub fn finalize_xof(self) -> SpongeReader<RATE> {
+        self.sponge.finalize()
+    }
+}
+
+impl<const RATE:usize> Default for Shake<RATE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub mod unofficial_sha {
     use super::*;
+
+    pub type Shake512 = Shake<9>;
+
     /// from the designs of shake128 and shake256 who have a hidden state of 256 and 512 bits respectively a logical continuation is shake512 with a hidden state of 1024 bits
     /// It is very important to note that this is not an official hash function, it's security has not been proven.
-    pub fn shake512(m:&Vec<u8>, output_length:usize) -> Vec<u8> {
+    #[cfg(feature = "alloc")]
+    pub fn shake512(m:&[u8], output_length:usize) -> Vec<u8> {
         let suffix = vec![true;4];
         keccak_c::<9>(m, &suffix, output_length)
     }
+
+    #[cfg(feature = "alloc")]
+    pub fn shake512_bits(m:&BitString, output_length:usize) -> BitString {
+        let suffix = vec![true;4];
+        keccak_c_bits::<9>(m, &suffix, output_length)
+    }
 }
 
+/// The SHA-3 functions as they were originally implemented in this crate. 
+/// These read every byte starting at the most significant bit and return their output in that same order, 
+/// so the standard digest is only found when the input is encoded with [le_encoding](crate::preprocessing::le_encoding) 
+/// and the output printed with [print_bytes_le](crate::printer::print_bytes_le)
+///
+/// # Example
+/// ```
+/// use jisp_sha3::preprocessing::le_encoding;
+/// use jisp_sha3::sha3::legacy::sha3_224;
+/// use jisp_sha3::printer::print_bytes_le;
+///
+/// let hex = le_encoding("abc");
+/// let hash = sha3_224(&hex);
+/// let res = print_bytes_le(&hash);
+///
+/// let expected = "e642824c 3f8cf24a d09234ee 7d3c766f c9a3a516 8d0c94ad 73b46fdf".to_owned();
+/// assert_eq!(res, expected);
+/// ```
+#[cfg(feature = "alloc")]
+pub mod legacy {
+    use crate::internals::sponge::legacy_keccak_c;
+    use alloc::{vec, vec::Vec};
+
+    pub fn sha3_224(m:&Vec<u8>) -> Vec<u8> {
+        let suffix = vec![false, true];
+        legacy_keccak_c::<18>(m, &suffix, 224)
+    }
+    pub fn sha3_256(m:&Vec<u8>) -> Vec<u8> {
+        let suffix = vec![false, true];
+        legacy_keccak_c::<17>(m, &suffix, 256)
+    }
+    pub fn sha3_384(m:&Vec<u8>) -> Vec<u8> {
+        let suffix = vec![false, true];
+        legacy_keccak_c::<13>(m, &suffix, 384)
+    }
+    pub fn sha3_512(m:&Vec<u8>) -> Vec<u8> {
+        let suffix = vec![false, true];
+        legacy_keccak_c::<9>(m, &suffix, 512)
+    }
+
+    pub fn shake128(m:&Vec<u8>, output_length:usize) -> Vec<u8> {
+        let suffix = vec![true;4];
+        legacy_keccak_c::<21>(m, &suffix, output_length)
+    }
+
+    pub fn shake256(m:&Vec<u8>, output_length:usize) -> Vec<u8> {
+        let suffix = vec![true;4];
+        legacy_keccak_c::<17>(m, &suffix, output_length)
+    }
+
+    /// The legacy version of [shake512](crate::sha3::unofficial_sha::shake512), which is not an official hash function
+    pub fn shake512(m:&Vec<u8>, output_length:usize) -> Vec<u8> {
+        let suffix = vec![true;4];
+        legacy_keccak_c::<9>(m, &suffix, output_length)
+    }
+}
 
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::preprocessing::{le_encoding, flip_ordering};
+    use crate::test_helpers::{from_str, to_hex};
 
+    fn chunked<const RATE:usize, const DIGEST:usize>(m:&[u8], chunk:usize) -> Vec<u8> {
+        let mut hasher = Sha3::<RATE, DIGEST>::new();
+        for part in m.chunks(chunk) {
+            hasher.update(part);
+        }
+        hasher.finalize()
+    }
 
+    #[test]
+    fn incremental_matches_one_shot() {
+        // crosses the block boundary of every variant
+        let m:Vec<u8> = (0..200).map(|i| i as u8).collect();
+        for chunk in [1, 7, 72, 200] {
+            assert_eq!(chunked::<18, 224>(&m, chunk), sha3_224(&m));
+            assert_eq!(chunked::<17, 256>(&m, chunk), sha3_256(&m));
+            assert_eq!(chunked::<13, 384>(&m, chunk), sha3_384(&m));
+            assert_eq!(chunked::<9, 512>(&m, chunk), sha3_512(&m));
+        }
+    }
 
+    #[test]
+    fn incremental_exact_blocks() {
+        for len in [0, 135, 136, 137, 272] {
+            let m = vec![0xa3; len];
+            assert_eq!(chunked::<17, 256>(&m, 136), sha3_256(&m));
+        }
+    }
 
+    #[test]
+    fn standard_digests() {
+        assert_eq!(to_hex(&sha3_224(b"abc")), "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf");
+        assert_eq!(to_hex(&sha3_256(b"abc")), "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532");
+        assert_eq!(to_hex(&sha3_384(b"abc")), 
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25");
+        assert_eq!(to_hex(&sha3_512(b"abc")), 
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0");
+        assert_eq!(to_hex(&shake256(b"", 512)), 
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762fd75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be");
+    }
+
+    #[test]
+    fn shake128_multiple_blocks() {
+        let expected = "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc844c50af32acd3f2cdd066568706f509b\
+            c1bdde58295dae3f891a9a0fca5783789a41f8611214ce612394df286a62d1a2252aa94db9c538956c717dc2bed4f232a0294c857c730aa1\
+            6067ac1062f1201fb0d377cfb9cde4c63599b27f3462bba4a0ed296c801f9ff7f57302bb3076ee145f97a32ae68e76ab66c48d51675bd49a\
+            cc29082f5647584e6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd4818cb006aa5b4cd";
+        assert_eq!(to_hex(&shake128(b"abc", 200*8)), expected);
+
+        let m = le_encoding("abc");
+        let hash = flip_ordering(&legacy::shake128(&m, 200*8));
+        assert_eq!(to_hex(&hash), expected);
+    }
+
+    #[test]
+    fn legacy_matches_standard() {
+        let m:Vec<u8> = (0..150).collect();
+        let flipped = flip_ordering(&m);
+
+        assert_eq!(flip_ordering(&legacy::sha3_224(&flipped)), sha3_224(&m));
+        assert_eq!(flip_ordering(&legacy::sha3_512(&flipped)), sha3_512(&m));
+        assert_eq!(flip_ordering(&legacy::shake256(&flipped, 2000)), shake256(&m, 2000));
+        assert_eq!(flip_ordering(&legacy::shake512(&flipped, 2000)), unofficial_sha::shake512(&m, 2000));
+    }
+
+    #[test]
+    fn xof_reads_match_single_call() {
+        let m:Vec<u8> = (0..50).collect();
+        let expected = shake256(&m, 700*8);
+
+        let mut hasher = Shake256::new();
+        hasher.update(&m);
+        let mut reader = hasher.finalize_xof();
+
+        let mut result = vec![0u8; 700];
+        let mut start = 0;
+        for len in [0, 1, 135, 136, 1, 300, 127] {
+            reader.read(&mut result[start..start + len]);
+            start += len;
+        }
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn xof_matches_unofficial_shake512() {
+        let m = vec![7u8; 100];
+        let mut reader = unofficial_sha::Shake512::new();
+        reader.update(&m);
+        let mut reader = reader.finalize_xof();
+
+        let mut result = vec![0u8; 200];
+        reader.read(&mut result);
+        assert_eq!(result, unofficial_sha::shake512(&m, 200*8));
+    }
+
+    // The bit oriented examples published by NIST for FIPS 202
+    #[test]
+    fn nist_bit_messages() {
+        let m5 = from_str("11001");
+        let m30 = from_str("110010100001101011011110100110");
+        let mut m1605 = BitString::from_bytes(&[0xa3; 200], 1600);
+        for bit in [true, true, false, false, false] {
+            m1605.push(bit);
+        }
+
+        assert_eq!(to_hex(sha3_224_bits(&m5).as_bytes()), "ffbad5da96bad71789330206dc6768ecaeb1b32dca6b3301489674ab");
+        assert_eq!(to_hex(sha3_224_bits(&m30).as_bytes()), "d666a514cc9dba25ac1ba69ed3930460deaac9851b5f0baab007df3b");
+        assert_eq!(to_hex(sha3_224_bits(&m1605).as_bytes()), "22d2f7bb0b173fd8c19686f9173166e3ee62738047d7eadd69efb228");
+        assert_eq!(to_hex(sha3_256_bits(&m5).as_bytes()), "7b0047cf5a456882363cbf0fb05322cf65f4b7059a46365e830132e3b5d957af");
+        assert_eq!(to_hex(sha3_256_bits(&m30).as_bytes()), "c8242fef409e5ae9d1f1c857ae4dc624b92b19809f62aa8c07411c54a078b1d0");
+        assert_eq!(to_hex(sha3_384_bits(&m5).as_bytes()), "737c9b491885e9bf7428e792741a7bf8dca9653471c3e148473f2c236b6a0a6455eb1dce9f779b4b6b237fef171b1c64");
+        assert_eq!(to_hex(sha3_512_bits(&m5).as_bytes()), 
+            "a13e01494114c09800622a70288c432121ce70039d753cadd2e006e4d961cb27544c1481e5814bdceb53be6733d5e099795e5e81918addb058e22a9f24883f37");
+
+        let shake = shake128_bits(&m5, 4096);
+        assert_eq!(to_hex(&shake.as_bytes()[..32]), "2e0abfba83e6720bfbc225ff6b7ab9ffce58ba027ee3d898764fef287ddeccca");
+    }
+
+    #[test]
+    fn bits_match_bytes() {
+        let m:Vec<u8> = (0..150).collect();
+        let bits = BitString::from_bytes(&m, m.len()*8);
+
+        assert_eq!(sha3_256_bits(&bits).as_bytes(), sha3_256(&m));
+        assert_eq!(shake256_bits(&bits, 2000).as_bytes(), shake256(&m, 2000));
+    }
+
+    #[test]
+    fn odd_output_length() {
+        let m = from_str("1");
+        let hash = shake128_bits(&m, 13);
+        assert_eq!(hash.len(), 13);
+        assert_eq!(hash, BitString::from_bytes(shake128_bits(&m, 16).as_bytes(), 13));
+    }
+}
@@ -0,0 +1,235 @@
+//! Authenticated encryption with associated data using SpongeWrap, from "Duplexing the sponge" by Bertoni, Daemen, Peeters and Van Assche.
+//!
+//! Everything runs on a single [Duplex] object on Keccak-f\[1600\] with a capacity of 256 bits.
+//! Every block of input is followed by a frame bit that tells the blocks apart:
+//! - The key of the paper is the key followed by the nonce, which fits in a single block and gets the frame bit 0 of the last key block
+//! - The associated data is absorbed in blocks of [BLOCK] bytes with a frame bit of 0, except for the last block which gets a 1.
+//!   The output of that call is the key stream for the first block of the message
+//! - Every block of the plaintext is encrypted with the current key stream and then absorbed, with a frame bit of 1 for the next key stream,
+//!   the last block gets a frame bit of 0 and its output is the tag
+//!
+//! The associated data and the message always have at least one block, which may be empty.
+//! A nonce must never be used twice with the same key, as that reveals the XOR of the two plaintexts.
+//! When [open] fails nothing of the decrypted message is released
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use jisp_sha3::spongewrap::{seal, open, TAG_LEN};
+//!
+//! let key = [7u8; 32];
+//! let nonce = [1u8; 16];
+//! let sealed = seal(&key, &nonce, b"header", b"attack at dawn");
+//! assert_eq!(sealed.len(), 14 + TAG_LEN);
+//!
+//! assert_eq!(open(&key, &nonce, b"header", &sealed).unwrap(), b"attack at dawn");
+//! assert!(open(&key, &nonce, b"other header", &sealed).is_err());
+//! # }
+//! ```
+use crate::error::Error;
+use crate::internals::duplex::Duplex;
+use crate::internals::verify::constant_time_eq;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+pub const KEY_LEN:usize = 32;
+pub const NONCE_LEN:usize = 16;
+pub const TAG_LEN:usize = 16;
+
+/// The rate of the duplex object in words, which leaves a capacity of 256 bits
+const RATE:usize = 21;
+
+/// The number of bytes in a block, the most that fits in the rate together with the frame bit and the padding
+pub const BLOCK:usize = RATE*8 - 1;
+
+/// Encrypts `plaintext` and returns the ciphertext followed by the tag of [TAG_LEN] bytes
+#[cfg(feature = "alloc")]
+pub fn seal(key:&[u8;KEY_LEN], nonce:&[u8;NONCE_LEN], ad:&[u8], plaintext:&[u8]) -> Vec<u8> {
+    let mut res = plaintext.to_vec();
+    let tag = seal_in_place(key, nonce, ad, &mut res);
+    res.extend_from_slice(&tag);
+    res
+}
+
+/// Checks the tag at the end of `sealed` and returns the decrypted message
+///
+/// # Errors
+/// [Error::AuthenticationFailed] if the tag does not match, or if `sealed` is too short to contain a tag
+#[cfg(feature = "alloc")]
+pub fn open(key:&[u8;KEY_LEN], nonce:&[u8;NONCE_LEN], ad:&[u8], sealed:&[u8]) -> Result<Vec<u8>, Error> {
+    if sealed.len() < TAG_LEN {
+        return Err(Error::AuthenticationFailed);
+    }
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+
+    let mut res = ciphertext.to_vec();
+    open_in_place(key, nonce, ad, &mut res, tag.try_into().unwrap())?;
+    Ok(res)
+}
+
+/// Encrypts the message in `buffer` in place and returns the tag, without allocating
+pub fn seal_in_place(key:&[u8;KEY_LEN], nonce:&[u8;NONCE_LEN], ad:&[u8], buffer:&mut [u8]) -> [u8;TAG_LEN] {
+    wrap(key, nonce, ad, buffer, false)
+}
+
+/// Decrypts the ciphertext in `buffer` in place, without allocating
+///
+/// # Errors
+/// [Error::AuthenticationFailed] if the tag does not match, the buffer is then filled with zeros
+pub fn open_in_place(key:&[u8;KEY_LEN], nonce:&[u8;NONCE_LEN], ad:&[u8], buffer:&mut [u8], tag:&[u8;TAG_LEN]) -> Result<(), Error> {
+    let expected = wrap(key, nonce, ad, buffer, true);
+    if !constant_time_eq(&expected, tag) {
+        buffer.fill(0);
+        return Err(Error::AuthenticationFailed);
+    }
+    Ok(())
+}
+
+/// Runs SpongeWrap over the buffer, which holds the plaintext when encrypting and the ciphertext when decrypting
+fn wrap(key:&[u8;KEY_LEN], nonce:&[u8;NONCE_LEN], ad:&[u8], buffer:&mut [u8], decrypt:bool) -> [u8;TAG_LEN] {
+    let mut duplex = Duplex::<RATE>::new(&[false]);
+    let mut init = [0u8; KEY_LEN + NONCE_LEN];
+    init[..KEY_LEN].copy_from_slice(key);
+    init[KEY_LEN..].copy_from_slice(nonce);
+    duplex.duplexing_into(&init, &mut []);
+
+    //associated data
+    let mut z = [0u8;BLOCK];
+    let len = buffer.len();
+    let blocks = ad.len().div_ceil(BLOCK).max(1);
+    for i in 0..blocks {
+        let a = &ad[(i*BLOCK).min(ad.len())..((i + 1)*BLOCK).min(ad.len())];
+        if i + 1 < blocks {
+            duplex.set_suffix(&[false]);
+            duplex.duplexing_into(a, &mut []);
+        } else {
+            duplex.set_suffix(&[true]);
+            duplex.duplexing_into(a, &mut z[..len.min(BLOCK)]);
+        }
+    }
+
+    //message
+    let mut tag = [0u8;TAG_LEN];
+    let blocks = len.div_ceil(BLOCK).max(1);
+    for i in 0..blocks {
+        let start = (i*BLOCK).min(len);
+        let end = ((i + 1)*BLOCK).min(len);
+        let mut plaintext = [0u8;BLOCK];
+        for (j, byte) in buffer[start..end].iter_mut().enumerate() {
+            plaintext[j] = if decrypt {*byte ^ z[j]} else {*byte};
+            *byte ^= z[j];
+        }
+
+        if i + 1 < blocks {
+            duplex.set_suffix(&[true]);
+            duplex.duplexing_into(&plaintext[..end - start], &mut z[..(len - end).min(BLOCK)]);
+        } else {
+            duplex.set_suffix(&[false]);
+            duplex.duplexing_into(&plaintext[..end - start], &mut tag);
+        }
+    }
+    tag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::from_hex;
+
+    const KEY:[u8;KEY_LEN] = [0x42; KEY_LEN];
+    const NONCE:[u8;NONCE_LEN] = [0x24; NONCE_LEN];
+
+    // Computed with an independent Python implementation of Keccak-f[1600] and Algorithm 3 of the paper,
+    // with the key 00 01 .. 1f, the nonce f0 f1 .. ff and the ciphertext followed by the tag
+    #[test]
+    fn known_answers() {
+        let key:[u8;KEY_LEN] = core::array::from_fn(|i| i as u8);
+        let nonce:[u8;NONCE_LEN] = core::array::from_fn(|i| 0xf0 + i as u8);
+        let ad:[u8;200] = core::array::from_fn(|i| i as u8);
+        let message:[u8;400] = core::array::from_fn(|i| (3*i) as u8);
+
+        let check = |ad:&[u8], message:&[u8], expected:&str| {
+            let expected = from_hex(expected);
+            let mut buffer = message.to_vec();
+            let tag = seal_in_place(&key, &nonce, ad, &mut buffer);
+            assert_eq!(buffer, expected[..message.len()]);
+            assert_eq!(tag[..], expected[message.len()..]);
+        };
+        check(b"", b"", "5c3463cb94ea0b091a333c9d219913ed");
+        check(b"header", b"attack at dawn", "94856e246c0b5ebbd9dfb2e1af249d1db39d50abd841b6561a3edaac94b1");
+        check(&ad, &message, "\
+            f83f90478b6fdb4807e112f45b04502c17ac4f6cb1571724f1f9018c2c85906728db6e779ce88df2c7fbd394db17d6429abee96e7ed84fea66dba395371f55d5\
+            6d2950617a25d3411bbe368868c42ab84fdec5332595d219dbf69404fe3f2ae35dab1b93d381e054081e0a547d238274cd5ed1fe26003b25ab2db9bcb6d135b4\
+            3c692a5f4954cf1d3f632f124081d264881e8034c2cab5fc03b5a817555a04290841e675a75d6475330b3ed0225b9babf8253775d3131e030f5aa503521faaf0\
+            90d929466bcc7e4848b2dc65a2eebf58456b6d690ac33c64de9950e8bad9d6a042e866ac5069b15b9b9fa531ba2f73cae49ef92780c172e365e83023a5849eda\
+            c442ca2ac0a3ef52cee118cb48d368daef0435eb7b9d3f51275d0596961d48db8315da62292eb8c470c24f489bfb188e1d9c86e0206993fd77cb2c7c82142a23\
+            97b3eef35acaa7fca37bebec8ea128c061706831cb9fbd820557ed9656c5ad9cd02a7a40dab7f79096d5bb45fb19998adcdfdbc80ca5afafb1401df469bfa30f\
+            fa93ca3f5009f6e33e80007cc63ce520ddbddfc4b22ef2f5621df508446cf38f");
+    }
+
+    #[test]
+    fn round_trip_in_place() {
+        let message:[u8;600] = core::array::from_fn(|i| (i*3) as u8);
+        // around the block boundaries of both the associated data and the message
+        for len in [0, 1, BLOCK - 1, BLOCK, BLOCK + 1, 2*BLOCK, 600] {
+            for ad_len in [0, BLOCK, BLOCK + 1] {
+                let ad = &message[..ad_len];
+                let mut buffer = [0u8;600];
+                buffer[..len].copy_from_slice(&message[..len]);
+
+                let tag = seal_in_place(&KEY, &NONCE, ad, &mut buffer[..len]);
+                if len > 0 {
+                    assert_ne!(buffer[..len], message[..len]);
+                }
+                open_in_place(&KEY, &NONCE, ad, &mut buffer[..len], &tag).unwrap();
+                assert_eq!(buffer[..len], message[..len]);
+            }
+        }
+    }
+
+    #[test]
+    fn failure_releases_nothing() {
+        let mut buffer = *b"attack at dawn";
+        let mut tag = seal_in_place(&KEY, &NONCE, b"", &mut buffer);
+        tag[0] ^= 1;
+
+        assert_eq!(open_in_place(&KEY, &NONCE, b"", &mut buffer, &tag), Err(Error::AuthenticationFailed));
+        assert_eq!(buffer, [0u8; 14]);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn tampering_is_detected() {
+        let ad = b"associated data";
+        let message:Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let sealed = seal(&KEY, &NONCE, ad, &message);
+        assert_eq!(open(&KEY, &NONCE, ad, &sealed).unwrap(), message);
+
+        // every bit of the ciphertext and the tag
+        for i in 0..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1 << (i % 8);
+            assert_eq!(open(&KEY, &NONCE, ad, &tampered), Err(Error::AuthenticationFailed));
+        }
+
+        assert!(open(&KEY, &NONCE, b"associated dat", &sealed).is_err());
+        assert!(open(&KEY, &[0x25; NONCE_LEN], ad, &sealed).is_err());
+        assert!(open(&[0x43; KEY_LEN], &NONCE, ad, &sealed).is_err());
+        assert!(open(&KEY, &NONCE, ad, &sealed[..sealed.len() - 1]).is_err());
+        assert!(open(&KEY, &NONCE, ad, &sealed[1..]).is_err());
+        assert!(open(&KEY, &NONCE, ad, &sealed[..TAG_LEN - 1]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn associated_data_and_message_are_separated() {
+        // moving bytes between the associated data and the message changes the tag
+        let a = seal(&KEY, &NONCE, b"ab", b"c");
+        let b = seal(&KEY, &NONCE, b"a", b"bc");
+        assert_ne!(a[1..], b[2..]);
+
+        let a = seal(&KEY, &NONCE, b"", b"");
+        let b = seal(&KEY, &[0; NONCE_LEN], b"", b"");
+        assert_ne!(a, b);
+    }
+}
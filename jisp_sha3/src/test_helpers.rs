@@ -0,0 +1,35 @@
+//! Conversions shared by the tests of all modules
+
+#[cfg(feature = "alloc")]
+use crate::preprocessing::BitString;
+#[cfg(feature = "alloc")]
+use std::{format, string::String};
+use std::vec::Vec;
+
+/// The bytes in lowercase hexadecimal without separators, in the order they are stored
+#[cfg(feature = "alloc")]
+pub fn to_hex(bytes:&[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The bytes of a hexadecimal string
+///
+/// # Panics
+/// If the string has an odd length or contains a character that is not hexadecimal
+pub fn from_hex(hex:&str) -> Vec<u8> {
+    assert!(hex.len().is_multiple_of(2), "{} is not a whole number of bytes", hex);
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
+
+/// A bit string written as a string of `0`s and `1`s
+#[cfg(feature = "alloc")]
+pub fn from_str(bits:&str) -> BitString {
+    let bits:Vec<bool> = bits.chars().map(|c| c == '1').collect();
+    BitString::from_bits(&bits)
+}
+
+/// The repeating pattern `00 01 .. FA` of length `n` used in the test vectors of RFC 9861
+#[cfg(feature = "alloc")]
+pub fn ptn(n:usize) -> Vec<u8> {
+    (0..n).map(|i| (i % 251) as u8).collect()
+}
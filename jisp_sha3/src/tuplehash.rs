@@ -0,0 +1,94 @@
+//! TupleHash128 and TupleHash256 as defined in NIST SP 800-185, for hashing a tuple of byte strings. 
+//!
+//! Every string is encoded together with its length before it is hashed with [cSHAKE](crate::cshake), 
+//! so moving bytes from one string to the next changes the hash. 
+//! The TupleHashXOF variants have an output of arbitrary length, where `l` bits are returned
+//!
+//! # Example
+//! ```
+//! use jisp_sha3::tuplehash::tuplehash128;
+//! use jisp_sha3::sha3::sha3_256;
+//!
+//! // concatenating the fields gives the same hash
+//! assert_eq!(sha3_256(&[b"ab".as_slice(), b"c"].concat()), sha3_256(&[b"a".as_slice(), b"bc"].concat()));
+//!
+//! // while the tuples are hashed to different values
+//! let first = tuplehash128(&[b"ab", b"c"], 256, b"");
+//! let second = tuplehash128(&[b"a", b"bc"], 256, b"");
+//! assert_ne!(first, second);
+//! ```
+use crate::cshake::{cshake128, cshake256};
+use crate::preprocessing::{encode_string, right_encode};
+use alloc::vec::Vec;
+
+pub fn tuplehash128(x:&[&[u8]], l:usize, s:&[u8]) -> Vec<u8> {
+    cshake128(&encode_tuple(x, l), l, b"TupleHash", s)
+}
+
+pub fn tuplehash256(x:&[&[u8]], l:usize, s:&[u8]) -> Vec<u8> {
+    cshake256(&encode_tuple(x, l), l, b"TupleHash", s)
+}
+
+pub fn tuplehashxof128(x:&[&[u8]], l:usize, s:&[u8]) -> Vec<u8> {
+    cshake128(&encode_tuple(x, 0), l, b"TupleHashXOF", s)
+}
+
+pub fn tuplehashxof256(x:&[&[u8]], l:usize, s:&[u8]) -> Vec<u8> {
+    cshake256(&encode_tuple(x, 0), l, b"TupleHashXOF", s)
+}
+
+/// `encode_string(x[0]) || ... || encode_string(x[n-1]) || right_encode(l)`
+fn encode_tuple(x:&[&[u8]], l:usize) -> Vec<u8> {
+    let mut res = Vec::new();
+    for string in x {
+        res.extend(encode_string(string));
+    }
+    res.extend(right_encode(l));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::to_hex;
+
+    // The samples published by NIST for SP 800-185
+    #[test]
+    fn nist_samples() {
+        let a:&[u8] = &[0x00, 0x01, 0x02];
+        let b:&[u8] = &[0x10, 0x11, 0x12, 0x13, 0x14, 0x15];
+        let c:&[u8] = &[0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28];
+        let s = b"My Tuple App";
+
+        assert_eq!(to_hex(&tuplehash128(&[a, b], 256, b"")), "c5d8786c1afb9b82111ab34b65b2c0048fa64e6d48e263264ce1707d3ffc8ed1");
+        assert_eq!(to_hex(&tuplehash128(&[a, b], 256, s)), "75cdb20ff4db1154e841d758e24160c54bae86eb8c13e7f5f40eb35588e96dfb");
+        assert_eq!(to_hex(&tuplehash128(&[a, b, c], 256, s)), "e60f202c89a2631eda8d4c588ca5fd07f39e5151998deccf973adb3804bb6e84");
+        assert_eq!(to_hex(&tuplehash256(&[a, b], 512, b"")), 
+            "cfb7058caca5e668f81a12a20a2195ce97a925f1dba3e7449a56f82201ec607311ac2696b1ab5ea2352df1423bde7bd4bb78c9aed1a853c78672f9eb23bbe194");
+        assert_eq!(to_hex(&tuplehash256(&[a, b, c], 512, s)), 
+            "45000be63f9b6bfd89f54717670f69a9bc763591a4f05c50d68891a744bcc6e7d6d5b5e82c018da999ed35b0bb49c9678e526abd8e85c13ed254021db9e790ce");
+
+        assert_eq!(to_hex(&tuplehashxof128(&[a, b], 256, b"")), "974004447b181f651c8019db58a3f5fea0fb9ca3537342112ce5bffc68f7896c");
+        assert_eq!(to_hex(&tuplehashxof256(&[a, b, c], 512, s)), 
+            "ffbd50d14f7c97bddbe7e816dc961f2e7ffe450e9104a34b2ecaf3baae0bf10379fcf23b98b9de1c0a28d35154a27b69e2f5b394426fd1ecc7b38c3cc37e83cc");
+    }
+
+    #[test]
+    fn empty_strings_count() {
+        let empty:&[u8] = b"";
+        let one = tuplehash256(&[b"abc"], 256, b"");
+        let two = tuplehash256(&[b"abc", empty], 256, b"");
+        let none = tuplehash256(&[], 256, b"");
+
+        assert_ne!(one, two);
+        assert_ne!(none, tuplehash256(&[empty], 256, b""));
+    }
+
+    #[test]
+    fn xof_prefix() {
+        let x:&[&[u8]] = &[b"field", b"another field"];
+        let long = tuplehashxof128(x, 1000*8, b"s");
+        assert_eq!(tuplehashxof128(x, 64, b"s"), long[..8]);
+        assert_ne!(tuplehash128(x, 64, b"s"), long[..8]);
+    }
+}
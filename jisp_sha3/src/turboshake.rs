@@ -0,0 +1,176 @@
+//! TurboSHAKE128 and TurboSHAKE256 as defined in RFC 9861, SHAKE with the keccak permutation reduced to 12 rounds. 
+//!
+//! Instead of the fixed suffix of SHAKE it takes a domain separation byte `d` between `0x01` and `0x7F`, 
+//! so different uses of the function can be kept apart. The byte contains the suffix bits followed by the first bit of the padding, 
+//! so `0x1F` gives the same suffix as SHAKE. It is used as the building block of [KangarooTwelve](crate::kangarootwelve)
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "alloc")] {
+//! use jisp_sha3::turboshake::turboshake128;
+//! use jisp_sha3::printer::print_bytes_be;
+//!
+//! let hash = turboshake128(b"", 0x1F, 256);
+//!
+//! let expected = "1e415f1c 5983aff2 16921727 7d17bb53 8cd945a3 97ddec54 1f1ce41a f2c1b74c";
+//! assert_eq!(print_bytes_be(&hash), expected);
+//! # }
+//! ```
+use crate::internals::sponge::{Sponge, SpongeReader};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// # Panics
+/// If `d` is not in the range `0x01..=0x7F`
+#[cfg(feature = "alloc")]
+pub fn turboshake128(m:&[u8], d:u8, l:usize) -> Vec<u8> {
+    let mut hasher = TurboShake128::new(d);
+    hasher.update(m);
+    hasher.finalize(l)
+}
+
+/// # Panics
+/// If `d` is not in the range `0x01..=0x7F`
+#[cfg(feature = "alloc")]
+pub fn turboshake256(m:&[u8], d:u8, l:usize) -> Vec<u8> {
+    let mut hasher = TurboShake256::new(d);
+    hasher.update(m);
+    hasher.finalize(l)
+}
+
+/// Incremental version of TurboSHAKE, use one of the aliases [TurboShake128] or [TurboShake256]
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use jisp_sha3::turboshake::{TurboShake128, turboshake128};
+///
+/// let mut hasher = TurboShake128::new(0x0B);
+/// hasher.update(b"first part, ");
+/// hasher.update(b"second part");
+///
+/// let mut reader = hasher.finalize_xof();
+/// let mut mask = [0u8; 100];
+/// reader.read(&mut mask);
+///
+/// assert_eq!(mask.to_vec(), turboshake128(b"first part, second part", 0x0B, 800));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TurboShake<const RATE:usize> {
+    sponge:Sponge<RATE>,
+}
+
+pub type TurboShake128 = TurboShake<21>;
+pub type TurboShake256 = TurboShake<17>;
+
+impl<const RATE:usize> TurboShake<RATE> {
+    /// # Panics
+    /// If `d` is not in the range `0x01..=0x7F`
+    pub fn new(d:u8) -> Self {
+        let (bits, len) = split_domain(d);
+        TurboShake { sponge: Sponge::with_rounds(&bits[..len], 12) }
+    }
+
+    /// Adds the next part of the message, can be called any number of times
+    pub fn update(&mut self, m:&[u8]) {
+        self.sponge.absorb(m);
+    }
+
+    /// Finishes the message and returns `l` bits of output
+    #[cfg(feature = "alloc")]
+    pub fn finalize(self, l:usize) -> Vec<u8> {
+        self.sponge.squeeze(l)
+    }
+
+    /// Finishes the message and returns a reader for an output of unknown length
+    pub fn finalize_xof(self) -> SpongeReader<RATE> {
+        self.sponge.finalize()
+    }
+}
+
+/// Splits a domain separation byte into the suffix bits in front of its highest set bit, which is the first bit of the padding
+///
+/// # Panics
+/// If `d` is not in the range `0x01..=0x7F`
+#[cfg(feature = "alloc")]
+pub fn domain_suffix(d:u8) -> Vec<bool> {
+    let (bits, len) = split_domain(d);
+    bits[..len].to_vec()
+}
+
+/// [domain_suffix] without allocating, the suffix is made up of the first `len` bits of the array
+fn split_domain(d:u8) -> ([bool;6], usize) {
+    if !(0x01..=0x7F).contains(&d) {panic!("The domain separation byte {:#04x} is not in the range 0x01..=0x7F!", d)}
+    let len = 7 - d.leading_zeros() as usize;
+    let bits = core::array::from_fn(|i| (d >> i) & 1 == 1);
+    (bits, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::test_helpers::{ptn, to_hex};
+    #[cfg(feature = "alloc")]
+    use std::vec;
+
+    // The test vectors from RFC 9861
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn turboshake128_rfc_vectors() {
+        assert_eq!(to_hex(&turboshake128(b"", 0x1F, 512)), 
+            "1e415f1c5983aff2169217277d17bb538cd945a397ddec541f1ce41af2c1b74c3e8ccae2a4dae56c84a04c2385c03c15e8193bdf58737363321691c05462c8df");
+        assert_eq!(to_hex(&turboshake128(b"", 0x1F, 10032*8)[10000..]), "a3b9b0385900ce761f22aed548e754da10a5242d62e8c658e3f3a923a7555607");
+
+        assert_eq!(to_hex(&turboshake128(&ptn(1), 0x1F, 256)), "55cedd6f60af7bb29a4042ae832ef3f58db7299f893ebb9247247d856958daa9");
+        assert_eq!(to_hex(&turboshake128(&ptn(17), 0x1F, 256)), "9c97d036a3bac819db70ede0ca554ec6e4c2a1a4ffbfd9ec269ca6a111161233");
+        assert_eq!(to_hex(&turboshake128(&ptn(17*17), 0x1F, 256)), "96c77c279e0126f7fc07c9b07f5cdae1e0be60bdbe10620040e75d7223a624d2");
+        assert_eq!(to_hex(&turboshake128(&ptn(17*17*17), 0x1F, 256)), "d4976eb56bcf118520582b709f73e1d6853e001fdaf80e1b13e0d0599d5fb372");
+
+        assert_eq!(to_hex(&turboshake128(&[0xff], 0x01, 256)), "012ad664922ce3f81b058735b50aacbde383f1a9a75180b4b9f929550a5552b5");
+        assert_eq!(to_hex(&turboshake128(&[0xff; 3], 0x06, 256)), "3d03988bb59e681851a192f429ae03988e8f444bc06036a3f1a7d2ccd758d174");
+        assert_eq!(to_hex(&turboshake128(&[0xff; 7], 0x0B, 256)), "8deeaa1aec47ccee569f659c21dfa8e112db3cee37b18178b2acd805b799cc37");
+        assert_eq!(to_hex(&turboshake128(&[0xff; 7], 0x30, 256)), "bb58fc02347880e41b8b53eaed4722828fe94ee0dc0ac8770644ce3dc1a77856");
+        assert_eq!(to_hex(&turboshake128(&[0xff; 7], 0x7F, 256)), "5834288ab65ff73eed3546a37f87c9195eae81d195f394451101a06bd226f8e0");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn turboshake256_rfc_vectors() {
+        assert_eq!(to_hex(&turboshake256(b"", 0x1F, 512)), 
+            "367a329dafea871c7802ec67f905ae13c57695dc2c6663c61035f59a18f8e7db11edc0e12e91ea60eb6b32df06dd7f002fbafabb6e13ec1cc20d995547600db0");
+        assert_eq!(to_hex(&turboshake256(&ptn(17), 0x1F, 512)), 
+            "b3bab0300e6a191fbe6137939835923578794ea54843f5011090fa2f3780a9e5cb22c59d78b40a0fbff9e672c0fbe0970bd2c845091c6044d687054da5d8e9c7");
+        assert_eq!(to_hex(&turboshake256(&ptn(17*17*17), 0x1F, 512)), 
+            "c74ebc919a5b3b0dd1228185ba02d29ef442d69d3d4276a93efe0bf9a16a7dc0cd4eabadab8cd7a5edd96695f5d360abe09e2c6511a3ec397da3b76b9e1674fb");
+        assert_eq!(to_hex(&turboshake256(&[0xff; 7], 0x7F, 512)), 
+            "838d25dc0eaf48326d78b0f7909eb579eae3a2f16924f06ae128ddea612658b3cc9f651813bb54c1b6f7d36f8b5e1fd60c8df0c2f61094380d294ae21bbc9e62");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn domain_suffixes() {
+        assert_eq!(domain_suffix(0x07), vec![true, true]);
+        assert_eq!(domain_suffix(0x06), vec![false, true]);
+        assert_eq!(domain_suffix(0x0B), vec![true, true, false]);
+        assert_eq!(domain_suffix(0x1F), vec![true;4]);
+        assert_eq!(domain_suffix(0x01), vec![]);
+        assert_eq!(domain_suffix(0x7F).len(), 6);
+    }
+
+    #[test]
+    fn reader_without_alloc() {
+        let mut hasher = TurboShake256::new(0x1F);
+        hasher.update(&[]);
+        let mut hash = [0u8; 8];
+        hasher.finalize_xof().read(&mut hash);
+        assert_eq!(hash, [0x36, 0x7a, 0x32, 0x9d, 0xaf, 0xea, 0x87, 0x1c]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn domain_byte_out_of_range() {
+        TurboShake128::new(0x80);
+    }
+}
@@ -4,13 +4,13 @@ use std::sync::mpsc::{self, Sender, Receiver};
 use eframe::egui;
 use eframe::epaint::FontId;
 use jisp_sha3 as sha;
-use sha::sha3::*;
+use sha::sha3::legacy::*;
 use sha::printer::{print_bytes_be, print_bytes_le};
 
 fn main() {
     let mut native_options = eframe::NativeOptions::default();
     let _ = native_options.viewport.inner_size.insert((660., 480.).into());
-    let _ = eframe::run_native("SHA-3", native_options, Box::new(|cc| Box::new(ShaGUI::new(cc))))
+    eframe::run_native("SHA-3", native_options, Box::new(|cc| Box::new(ShaGUI::new(cc))))
         .expect("Unexpected Error");
 }
 
@@ -98,7 +98,7 @@ fn hashing_thread(tx:Sender<Message>, rx:Receiver<(AlgInfo, String)>) {
             Algorithm::Sha3_512 => sha3_512(&bytes),
             Algorithm::Shake128 => shake128(&bytes, a.digest),
             Algorithm::Shake256 => shake256(&bytes, a.digest),
-            Algorithm::Shake512 => unofficial_sha::shake512(&bytes, a.digest)
+            Algorithm::Shake512 => shake512(&bytes, a.digest)
         };
         tx.send(Message::Hash(print(&hash))).unwrap();
     }